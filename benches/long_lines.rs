@@ -0,0 +1,57 @@
+//! Reading and rewriting a reference file whose serialized mesh data is one multi-megabyte
+//! line, followed by a few references. Run with `cargo bench --bench long_lines`.
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use guid_sync::guid_mapper::DEFAULT_TOP;
+use guid_sync::{GuidSyncer, SyncOptions};
+use std::fs;
+use std::path::Path;
+use tempfile::TempDir;
+
+const MAIN_GUID: &str = "aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa";
+const SUB_GUID: &str = "bbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb";
+
+/// Size of the mesh data line, in bytes
+const LINE_LEN: usize = 8 << 20;
+
+/// Main and subordinate projects with one differing GUID, the subordinate referencing it
+/// after a `LINE_LEN` line
+fn fixture() -> TempDir {
+    let dir = tempfile::tempdir().expect("create temp dir");
+    for (side, guid) in [("main", MAIN_GUID), ("sub", SUB_GUID)] {
+        let assets = dir.path().join(side).join("Assets");
+        fs::create_dir_all(&assets).unwrap();
+        fs::write(assets.join("Player.prefab"), "%YAML 1.1\n--- !u!1 &1\nGameObject:\n  m_Name: Player\n").unwrap();
+        fs::write(assets.join("Player.prefab.meta"), format!("fileFormatVersion: 2\nguid: {}\n", guid)).unwrap();
+    }
+
+    let mut mesh = format!("%YAML 1.1\n--- !u!43 &4300000\nMesh:\n  _typelessdata: {}\n", "0000803f".repeat(LINE_LEN / 8));
+    for i in 0..100 {
+        mesh.push_str(&format!("  m_Ref{}: {{fileID: 100100000, guid: {}, type: 3}}\n", i, SUB_GUID));
+    }
+    fs::write(dir.path().join("sub/Assets/Mesh.asset"), mesh).unwrap();
+    dir
+}
+
+fn scanned_syncer(dir: &Path) -> GuidSyncer {
+    let mut syncer = GuidSyncer::new(dir.join("main/Assets"), dir.join("sub/Assets"));
+    syncer.scan_projects().expect("scan fixture projects");
+    syncer
+}
+
+fn long_lines(c: &mut Criterion) {
+    let dir = fixture();
+    let syncer = scanned_syncer(dir.path());
+    let mut group = c.benchmark_group("multi-megabyte line");
+    group.sample_size(10);
+    group.bench_function("report with context", |b| {
+        b.iter(|| syncer.generate_sync_operations_report(true).unwrap())
+    });
+    group.bench_function("dry run sync", |b| {
+        b.iter(|| syncer.sync_guids(&SyncOptions { dry_run: true, top: DEFAULT_TOP, ..Default::default() }).unwrap())
+    });
+    group.finish();
+}
+
+criterion_group!(benches, long_lines);
+criterion_main!(benches);
@@ -0,0 +1,74 @@
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+
+use crate::error::{IoContext, Result};
+use crate::mapping_file::is_valid_guid;
+
+/// Where the main project's authoritative GUIDs come from
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum MainGuidSource {
+    /// The GUIDs in the meta files on disk
+    #[default]
+    Meta,
+    /// The meta files, cross-checked against the GUIDs Unity imported into `Library`
+    Db,
+}
+
+/// The GUIDs in a project's imported asset database. Only the per-GUID folder layout of the
+/// original asset pipeline (`Library/metadata/<first two hex digits>/<guid>`) can be read; the
+/// LMDB database of Asset Pipeline v2 (`Library/SourceAssetDB`) is not a documented format.
+pub struct AssetDatabase {
+    guids: HashSet<String>,
+}
+
+impl AssetDatabase {
+    /// Read the database next to a project's Assets folder. None if the project has no
+    /// readable database, with a warning saying why.
+    pub fn open(assets: &Path) -> Result<Option<Self>> {
+        let library = assets.parent().unwrap_or(assets).join("Library");
+        let metadata = library.join("metadata");
+        if !metadata.is_dir() {
+            if library.join("SourceAssetDB").is_file() {
+                tracing::warn!(
+                    "{} uses the Asset Pipeline v2 database, which cannot be read; GUIDs are taken from meta files only",
+                    library.display()
+                );
+            } else {
+                tracing::warn!("No imported asset database in {}; GUIDs are taken from meta files only", library.display());
+            }
+            return Ok(None);
+        }
+
+        let mut guids = HashSet::new();
+        let buckets = fs::read_dir(&metadata)
+            .io_context(|| format!("Failed to read asset database: {}", metadata.display()))?;
+        for bucket in buckets.filter_map(|entry| entry.ok()).filter(|entry| entry.path().is_dir()) {
+            let entries = fs::read_dir(bucket.path())
+                .io_context(|| format!("Failed to read asset database: {}", bucket.path().display()))?;
+            for entry in entries.filter_map(|entry| entry.ok()) {
+                // `<guid>.info` and `<guid>.resource` files sit next to the artifact itself
+                let name = entry.file_name();
+                let Some(guid) = name.to_str().map(|name| name.split('.').next().unwrap_or(name)) else {
+                    continue;
+                };
+                if is_valid_guid(guid) {
+                    guids.insert(guid.to_ascii_lowercase());
+                }
+            }
+        }
+        Ok(Some(Self { guids }))
+    }
+
+    pub fn contains(&self, guid: &str) -> bool {
+        self.guids.contains(guid)
+    }
+
+    pub fn len(&self) -> usize {
+        self.guids.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.guids.is_empty()
+    }
+}
@@ -0,0 +1,78 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::error::{IoContext, Result};
+
+/// Replace a file's contents without ever leaving it half written: the new contents go to a
+/// sibling temp file, which takes over the original's permissions and is then renamed into place
+/// (atomic on the same filesystem). A symlink is written through, so the link itself survives.
+/// A read-only file is refused like a plain write would be, the rename would replace it anyway.
+pub fn write_atomic(path: &Path, contents: &[u8]) -> Result<()> {
+    let resolved;
+    let path = if path.is_symlink() {
+        resolved = fs::canonicalize(path)
+            .io_context(|| format!("Failed to resolve symlink: {}", path.display()))?;
+        resolved.as_path()
+    } else {
+        path
+    };
+    if fs::metadata(path).is_ok_and(|metadata| metadata.permissions().readonly()) {
+        return Err(std::io::Error::from(std::io::ErrorKind::PermissionDenied))
+            .io_context(|| format!("File is read-only: {}", path.display()));
+    }
+    let temp_path = temp_path_for(path)?;
+    let result = write_and_rename(path, &temp_path, contents);
+    if result.is_err() {
+        // Best effort, the temp file is useless once the write failed
+        let _ = fs::remove_file(&temp_path);
+    }
+    result
+}
+
+fn write_and_rename(path: &Path, temp_path: &Path, contents: &[u8]) -> Result<()> {
+    fs::write(temp_path, contents)
+        .io_context(|| format!("Failed to write temp file: {}", temp_path.display()))?;
+
+    if let Ok(metadata) = fs::metadata(path) {
+        fs::set_permissions(temp_path, metadata.permissions())
+            .io_context(|| format!("Failed to copy permissions to: {}", temp_path.display()))?;
+    }
+
+    fs::rename(temp_path, path)
+        .io_context(|| format!("Failed to move {} into place", temp_path.display()))
+}
+
+/// Hidden temp file next to the target, so the rename never crosses filesystems
+fn temp_path_for(path: &Path) -> Result<PathBuf> {
+    let file_name = path
+        .file_name()
+        .ok_or_else(|| std::io::Error::from(std::io::ErrorKind::InvalidInput))
+        .io_context(|| format!("Not a file path: {}", path.display()))?;
+    let mut temp_name = std::ffi::OsString::from(".");
+    temp_name.push(file_name);
+    temp_name.push(format!(".guid-sync-{}.tmp", std::process::id()));
+    Ok(path.with_file_name(temp_name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(unix)]
+    #[test]
+    fn rename_keeps_the_original_permissions() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("Level.unity");
+        fs::write(&path, "old").unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o640)).unwrap();
+
+        write_atomic(&path, b"new").unwrap();
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "new");
+        assert_eq!(fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o640);
+        // Only the target is left, the temp file was renamed onto it
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+}
@@ -0,0 +1,154 @@
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use crate::error::{GuidSyncError, IoContext, Result};
+
+/// Progress of a live sync, so an interrupted one can continue where it stopped. The file is
+/// JSON Lines: a header with the planned GUID changes, then one line per finished step. Lines
+/// are appended as the sync goes, a line cut short by a kill is ignored when reading.
+pub struct Checkpoint {
+    path: PathBuf,
+    header: CheckpointHeader,
+    done: HashSet<CheckpointStep>,
+    file: Mutex<File>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct CheckpointHeader {
+    /// Fingerprint of `mappings`, so a checkpoint is never used for another set of changes
+    plan: String,
+    /// Relative meta path -> (main GUID, subordinate GUID)
+    mappings: BTreeMap<PathBuf, (String, String)>,
+}
+
+/// A unit of work finished by the sync, keyed by the path relative to the subordinate project
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(tag = "step", content = "path", rename_all = "kebab-case")]
+pub enum CheckpointStep {
+    /// The meta file's own GUID was rewritten
+    Meta(PathBuf),
+    /// The GUID references in the file were rewritten (or there were none)
+    References(PathBuf),
+}
+
+impl Checkpoint {
+    /// Start a new checkpoint for `mappings`, failing if the file exists already
+    pub fn create(path: &Path, mappings: &HashMap<PathBuf, (String, String)>) -> Result<Self> {
+        let mappings: BTreeMap<_, _> = mappings.iter().map(|(k, v)| (k.clone(), v.clone())).collect();
+        let header = CheckpointHeader { plan: plan_key(&mappings), mappings };
+        let mut file = OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(path)
+            .io_context(|| format!("Failed to create checkpoint: {}", path.display()))?;
+        writeln!(file, "{}", to_json(&header, path)?)
+            .io_context(|| format!("Failed to write checkpoint: {}", path.display()))?;
+        Ok(Self { path: path.to_path_buf(), header, done: HashSet::new(), file: Mutex::new(file) })
+    }
+
+    /// Load a checkpoint written by an earlier sync; new steps are appended to it
+    pub fn open(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path).io_context(|| format!("Failed to read checkpoint: {}", path.display()))?;
+        let mut lines = content.lines();
+        let invalid = |reason: &str| GuidSyncError::InvalidCheckpoint { path: path.to_path_buf(), reason: reason.to_string() };
+        let header: CheckpointHeader = lines
+            .next()
+            .and_then(|line| serde_json::from_str(line).ok())
+            .ok_or_else(|| invalid("is not a guid-sync checkpoint"))?;
+        if header.plan != plan_key(&header.mappings) {
+            return Err(invalid("has been modified and cannot be trusted"));
+        }
+        let done = lines.filter_map(|line| serde_json::from_str(line).ok()).collect();
+
+        let file = OpenOptions::new()
+            .append(true)
+            .open(path)
+            .io_context(|| format!("Failed to open checkpoint: {}", path.display()))?;
+        Ok(Self { path: path.to_path_buf(), header, done, file: Mutex::new(file) })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The GUID changes the checkpointed sync was started with
+    pub fn mappings(&self) -> HashMap<PathBuf, (String, String)> {
+        self.header.mappings.iter().map(|(k, v)| (k.clone(), v.clone())).collect()
+    }
+
+    /// Whether the checkpoint was written for exactly these GUID changes
+    pub fn matches(&self, mappings: &HashMap<PathBuf, (String, String)>) -> bool {
+        let mappings: BTreeMap<_, _> = mappings.iter().map(|(k, v)| (k.clone(), v.clone())).collect();
+        self.header.plan == plan_key(&mappings)
+    }
+
+    pub fn is_done(&self, step: &CheckpointStep) -> bool {
+        self.done.contains(step)
+    }
+
+    /// Number of steps finished before this checkpoint was loaded
+    pub fn completed(&self) -> usize {
+        self.done.len()
+    }
+
+    /// Append a finished step. Safe to call from several threads at once.
+    pub fn record(&self, step: &CheckpointStep) -> Result<()> {
+        let line = to_json(step, &self.path)?;
+        let mut file = self.file.lock().unwrap_or_else(|e| e.into_inner());
+        writeln!(file, "{}", line)
+            .and_then(|_| file.flush())
+            .io_context(|| format!("Failed to update checkpoint: {}", self.path.display()))
+    }
+
+    /// Delete the checkpoint once the sync it tracked has finished
+    pub fn remove(self) -> Result<()> {
+        drop(self.file);
+        fs::remove_file(&self.path)
+            .io_context(|| format!("Failed to remove checkpoint: {}", self.path.display()))
+    }
+}
+
+fn to_json(value: &impl Serialize, path: &Path) -> Result<String> {
+    serde_json::to_string(value).map_err(|source| GuidSyncError::Json {
+        message: format!("Failed to write checkpoint: {}", path.display()),
+        source,
+    })
+}
+
+/// SHA-256 of the mappings as `path<TAB>main<TAB>subordinate` lines in path order, with `/`
+/// separators, so the key is the same on every platform and Rust version
+fn plan_key(mappings: &BTreeMap<PathBuf, (String, String)>) -> String {
+    let mut hasher = Sha256::new();
+    for (path, (main_guid, sub_guid)) in mappings {
+        let path = path.to_string_lossy().replace('\\', "/");
+        hasher.update(format!("{}\t{}\t{}\n", path, main_guid, sub_guid));
+    }
+    format!("{:x}", hasher.finalize())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mappings(entries: &[(&str, &str, &str)]) -> BTreeMap<PathBuf, (String, String)> {
+        entries
+            .iter()
+            .map(|(path, main, sub)| (PathBuf::from(path), (main.to_string(), sub.to_string())))
+            .collect()
+    }
+
+    #[test]
+    fn plan_key_is_a_sha256_of_the_sorted_mappings() {
+        let key = plan_key(&mappings(&[("B.png.meta", "aa", "bb"), ("A/C.prefab.meta", "cc", "dd")]));
+        assert_eq!(key.len(), 64);
+        assert_eq!(key, plan_key(&mappings(&[("A/C.prefab.meta", "cc", "dd"), ("B.png.meta", "aa", "bb")])));
+        assert_eq!(key, plan_key(&mappings(&[("A\\C.prefab.meta", "cc", "dd"), ("B.png.meta", "aa", "bb")])));
+        assert_ne!(key, plan_key(&mappings(&[("A/C.prefab.meta", "cc", "de"), ("B.png.meta", "aa", "bb")])));
+        assert_eq!(plan_key(&mappings(&[("A.meta", "11", "22")])), format!("{:x}", Sha256::digest(b"A.meta\t11\t22\n")));
+    }
+}
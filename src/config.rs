@@ -0,0 +1,66 @@
+use serde::Deserialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::error::{GuidSyncError, IoContext, Result};
+use crate::guid_mapper::SyncDirection;
+
+/// Config file looked up in the working directory when no `--config` is given
+pub const CONFIG_FILE_NAME: &str = "guid-sync.toml";
+
+/// Default options from a `guid-sync.toml`. Each value is only used when the matching
+/// command-line flag is not given, and only by the commands that take that flag.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct Config {
+    pub main: Option<PathBuf>,
+    pub subordinate: Option<PathBuf>,
+    pub include: Vec<String>,
+    pub exclude: Vec<String>,
+    pub ref_ext: Vec<String>,
+    pub direction: Option<SyncDirection>,
+}
+
+impl Config {
+    /// Load the config at `path`. Relative project paths in it are resolved against the
+    /// directory the file is in.
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path).io_context(|| format!("Failed to read config: {}", path.display()))?;
+        let mut config: Self =
+            toml::from_str(&content).map_err(|source| GuidSyncError::InvalidConfig { path: path.to_path_buf(), source })?;
+        let dir = path.parent().unwrap_or(Path::new(""));
+        for project in [&mut config.main, &mut config.subordinate].into_iter().flatten() {
+            *project = dir.join(&*project);
+        }
+        Ok(config)
+    }
+
+    /// Load `explicit`, or `guid-sync.toml` in the working directory if it exists. Without
+    /// either every option keeps its built-in default.
+    pub fn discover(explicit: Option<&Path>) -> Result<Self> {
+        match explicit {
+            Some(path) => Self::load(path),
+            None if Path::new(CONFIG_FILE_NAME).is_file() => Self::load(Path::new(CONFIG_FILE_NAME)),
+            None => Ok(Self::default()),
+        }
+    }
+
+    /// Starter config written by `init`, with every option but the project paths commented out
+    pub fn template(main: Option<&Path>, subordinate: Option<&Path>) -> String {
+        let path_line = |key: &str, path: Option<&Path>| match path {
+            Some(path) => format!("{} = {}\n", key, toml::Value::String(path.to_string_lossy().into_owned())),
+            None => format!("# {} = \"../MyProject\"\n", key),
+        };
+        format!(
+            "# Defaults for guid-sync; command-line flags override them\n\
+             {}{}\
+             # include = [\"Art/**\"]\n\
+             # exclude = [\"**/Generated/**\"]\n\
+             # ref-ext = [\"prefab\", \"unity\", \"asset\"]\n\
+             # direction = \"main-wins\"\n",
+            path_line("main", main),
+            path_line("subordinate", subordinate),
+        )
+    }
+}
+
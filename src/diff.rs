@@ -0,0 +1,12 @@
+use similar::TextDiff;
+use std::path::Path;
+
+/// Unified diff of only the changed lines between two versions of a file, without context
+pub fn unified_diff(path: &Path, old: &str, new: &str) -> String {
+    let name = path.display().to_string();
+    TextDiff::from_lines(old, new)
+        .unified_diff()
+        .context_radius(0)
+        .header(&name, &name)
+        .to_string()
+}
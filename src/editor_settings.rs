@@ -0,0 +1,60 @@
+use regex::Regex;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use std::sync::LazyLock;
+
+use crate::error::{IoContext, Result};
+
+static SERIALIZATION_MODE_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?m)^\s*m_SerializationMode:\s*(\d+)").expect("valid serialization mode regex")
+});
+
+/// Unity's Asset Serialization setting (Project Settings > Editor), as `m_SerializationMode`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SerializationMode {
+    /// Binary assets stay binary, text assets stay text
+    Mixed,
+    ForceBinary,
+    ForceText,
+}
+
+impl SerializationMode {
+    /// Read the mode from `ProjectSettings/EditorSettings.asset` next to a project's Assets folder.
+    /// None if the project has no settings file or the value is missing or unknown.
+    pub fn read(assets: &Path) -> Result<Option<Self>> {
+        let Some(project) = assets.parent() else {
+            return Ok(None);
+        };
+        let path = project.join("ProjectSettings").join("EditorSettings.asset");
+        if !path.is_file() {
+            return Ok(None);
+        }
+
+        let bytes = fs::read(&path).io_context(|| format!("Failed to read {}", path.display()))?;
+        if bytes.contains(&0) {
+            // Only binary serialization writes the settings themselves as binary
+            return Ok(Some(Self::ForceBinary));
+        }
+        let content = String::from_utf8_lossy(&bytes);
+        let mode = SERIALIZATION_MODE_REGEX
+            .captures(&content)
+            .and_then(|captures| match &captures[1] {
+                "0" => Some(Self::Mixed),
+                "1" => Some(Self::ForceBinary),
+                "2" => Some(Self::ForceText),
+                _ => None,
+            });
+        Ok(mode)
+    }
+}
+
+impl fmt::Display for SerializationMode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Self::Mixed => "Mixed",
+            Self::ForceBinary => "Force Binary",
+            Self::ForceText => "Force Text",
+        })
+    }
+}
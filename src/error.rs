@@ -0,0 +1,174 @@
+use std::error::Error;
+use std::io;
+use std::path::PathBuf;
+
+/// What can go wrong in [`MetaFile`](crate::MetaFile), [`GuidSyncer`](crate::GuidSyncer) and the
+/// helper modules, so embedders can react to specific failures. The messages are the ones the
+/// CLI prints.
+#[derive(Debug, thiserror::Error)]
+pub enum GuidSyncError {
+    #[error("Meta file not found: {}", .0.display())]
+    MetaNotFound(PathBuf),
+
+    #[error("No GUID found in meta file: {}", .0.display())]
+    MissingGuid(PathBuf),
+
+    #[error("Malformed GUID {value:?} in meta file (expected 32 hex characters): {}", path.display())]
+    MalformedGuid { path: PathBuf, value: String },
+
+    /// Synced GUIDs would be shared by two assets of the rewritten project
+    #[error("{count} GUID collisions found in the subordinate project; resolve them manually or pass --allow-collisions")]
+    PathCollision { count: usize },
+
+    /// Subordinate GUIDs whose meta files map to different main GUIDs
+    #[error("{count} subordinate GUIDs map to different main GUIDs depending on the meta file; pass --resolve-ambiguous first or skip")]
+    AmbiguousGuids { count: usize },
+
+    #[error("Backup directory already exists: {} (use --force to overwrite)", .0.display())]
+    BackupExists(PathBuf),
+
+    /// Files the scan had to skip, with `fail_on_warning`
+    #[error("{} files could not be read (--fail-on-warning):\n{}", .0.len(), skipped_list(.0))]
+    UnreadableFiles(Vec<(PathBuf, String)>),
+
+    /// A checkpoint that does not belong to the sync being run
+    #[error("Checkpoint {} {reason}", path.display())]
+    CheckpointMismatch { path: PathBuf, reason: String },
+
+    /// A combined sync report without an entry for the project being undone or verified
+    #[error("Sync report {} has no entry for {}; it covers: {}", path.display(), project.display(), project_list(projects))]
+    ProjectNotInReport { path: PathBuf, project: PathBuf, projects: Vec<PathBuf> },
+
+    #[error("Low-memory scanning only supports matching by path")]
+    LowMemoryContentMatch,
+
+    /// A checkpoint file that is not one, or was edited after it was written
+    #[error("Checkpoint {} {reason}", path.display())]
+    InvalidCheckpoint { path: PathBuf, reason: String },
+
+    /// A line of a CSV mapping file that is not `old_guid,new_guid[,type]`
+    #[error("Line {line}: {reason}")]
+    InvalidMappingLine { line: usize, reason: String },
+
+    #[error("Invalid GUID in mapping file: {0:?} (expected 32 hex characters)")]
+    InvalidMappingGuid(String),
+
+    /// An old GUID mapped to two different new GUIDs or types
+    #[error("Duplicate old_guid with a different new_guid in mapping file: {0}")]
+    ConflictingMapping(String),
+
+    #[error("Invalid glob pattern: {pattern}")]
+    InvalidGlob {
+        pattern: String,
+        #[source]
+        source: globset::Error,
+    },
+
+    #[error("Invalid config: {}", path.display())]
+    InvalidConfig {
+        path: PathBuf,
+        #[source]
+        source: toml::de::Error,
+    },
+
+    #[error("{message}")]
+    Archive {
+        message: String,
+        #[source]
+        source: zip::result::ZipError,
+    },
+
+    #[error("Archive does not contain an Assets folder: {}", .0.display())]
+    ArchiveWithoutAssets(PathBuf),
+
+    /// A project used with `--commit` or `--since` that is not inside a git work tree
+    #[error("{} is not inside a git repository (required by {flag})", path.display())]
+    NotInGitRepository { path: PathBuf, flag: &'static str },
+
+    #[error("The git repository of {} has no work tree", .0.display())]
+    NoGitWorkTree(PathBuf),
+
+    #[error("{} is outside the git work tree {}", path.display(), work_tree.display())]
+    OutsideGitWorkTree { path: PathBuf, work_tree: PathBuf },
+
+    /// A `--since` value that does not name a commit
+    #[error("{rev:?} is not a commit in the git repository of {}", path.display())]
+    NotAGitCommit { rev: String, path: PathBuf },
+
+    /// Changes of its own in a project synced with `--commit`, as `git status --porcelain` lines
+    #[error("{} has uncommitted changes, commit or stash them before using --commit:\n{}", path.display(), change_list(changes))]
+    UncommittedChanges { path: PathBuf, changes: Vec<String> },
+
+    #[error("{message}")]
+    Git {
+        message: String,
+        #[source]
+        source: git2::Error,
+    },
+
+    #[error("--tui needs an interactive terminal")]
+    NotATerminal,
+
+    #[error("{message}")]
+    Io {
+        message: String,
+        #[source]
+        source: io::Error,
+    },
+
+    #[error("{message}")]
+    Json {
+        message: String,
+        #[source]
+        source: serde_json::Error,
+    },
+
+    #[error("Failed to build thread pool")]
+    ThreadPool(#[from] rayon::ThreadPoolBuildError),
+}
+
+pub type Result<T, E = GuidSyncError> = std::result::Result<T, E>;
+
+/// Attach the message of a `GuidSyncError::Io` to an I/O result
+pub(crate) trait IoContext<T> {
+    fn io_context(self, message: impl FnOnce() -> String) -> Result<T>;
+}
+
+impl<T> IoContext<T> for io::Result<T> {
+    fn io_context(self, message: impl FnOnce() -> String) -> Result<T> {
+        self.map_err(|source| GuidSyncError::Io { message: message(), source })
+    }
+}
+
+/// An error and its causes on one line, like anyhow's `{:#}`
+pub(crate) fn error_chain(error: &dyn Error) -> String {
+    let mut message = error.to_string();
+    let mut source = error.source();
+    while let Some(cause) = source {
+        message.push_str(": ");
+        message.push_str(&cause.to_string());
+        source = cause.source();
+    }
+    message
+}
+
+fn project_list(projects: &[PathBuf]) -> String {
+    projects.iter().map(|project| project.display().to_string()).collect::<Vec<_>>().join(", ")
+}
+
+/// The first ten changes, then how many more there are
+fn change_list(changes: &[String]) -> String {
+    let mut list = changes.iter().take(10).cloned().collect::<Vec<_>>().join("\n");
+    if changes.len() > 10 {
+        list.push_str(&format!("\n... and {} more", changes.len() - 10));
+    }
+    list
+}
+
+fn skipped_list(skipped: &[(PathBuf, String)]) -> String {
+    skipped
+        .iter()
+        .map(|(path, reason)| format!("  {}: {}", path.display(), reason))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
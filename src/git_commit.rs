@@ -0,0 +1,192 @@
+use git2::{Commit, DiffOptions, ErrorCode, Index, Repository, Status, StatusOptions};
+use std::path::{Path, PathBuf};
+
+use crate::error::{GuidSyncError, IoContext, Result};
+
+/// Fail unless `project` is inside a git work tree and has no uncommitted changes, so a
+/// commit made after the sync holds nothing but the sync itself
+pub fn ensure_clean(project: &Path) -> Result<()> {
+    let Ok(repo) = Repository::discover(project) else {
+        return Err(GuidSyncError::NotInGitRepository { path: project.to_path_buf(), flag: "--commit" });
+    };
+    let workdir = work_tree(&repo, project)?;
+
+    let mut options = StatusOptions::new();
+    options.include_untracked(true).recurse_untracked_dirs(true).include_ignored(false);
+    let inside = relative_to(&workdir, project)?;
+    if !inside.as_os_str().is_empty() {
+        options.pathspec(&inside);
+    }
+    let statuses = repo
+        .statuses(Some(&mut options))
+        .map_err(git_error(format!("Failed to read the git status of {}", project.display())))?;
+    let changes: Vec<String> = statuses
+        .iter()
+        .filter(|entry| entry.status() != Status::CURRENT)
+        .map(|entry| format!("{} {}", status_code(entry.status()), String::from_utf8_lossy(entry.path_bytes())))
+        .collect();
+    if !changes.is_empty() {
+        return Err(GuidSyncError::UncommittedChanges { path: project.to_path_buf(), changes });
+    }
+    Ok(())
+}
+
+/// Stage `files` and commit exactly them with `message`, returning the new commit's short hash
+pub fn commit_files(project: &Path, files: &[PathBuf], message: &str) -> Result<String> {
+    let repo = Repository::discover(project)
+        .map_err(|_| GuidSyncError::NotInGitRepository { path: project.to_path_buf(), flag: "--commit" })?;
+    // Checked first, so a missing author does not leave the files staged
+    let signature = repo
+        .signature()
+        .map_err(git_error("No git author configured (set user.name and user.email)"))?;
+    let workdir = work_tree(&repo, project)?;
+    let paths = files
+        .iter()
+        .map(|file| relative_to(&workdir, file))
+        .collect::<Result<Vec<_>>>()?;
+
+    let mut index = repo.index().map_err(git_error("Failed to read the git index"))?;
+    for path in &paths {
+        if workdir.join(path).exists() {
+            index.add_path(path)
+        } else {
+            index.remove_path(path)
+        }
+        .map_err(git_error(format!("Failed to stage {}", path.display())))?;
+    }
+    index.write().map_err(git_error("Failed to write the git index"))?;
+
+    // The commit is HEAD plus only these files, even if something else was staged meanwhile
+    let head = match repo.head() {
+        Ok(head) => Some(head.peel_to_commit().map_err(git_error("HEAD is not a commit"))?),
+        Err(e) if e.code() == ErrorCode::UnbornBranch => None,
+        Err(e) => return Err(git_error("Failed to read HEAD")(e)),
+    };
+    let tree = commit_tree(&repo, head.as_ref(), &index, &paths).map_err(git_error("Failed to build the commit tree"))?;
+
+    let parents: Vec<&Commit> = head.iter().collect();
+    let id = repo
+        .commit(Some("HEAD"), &signature, &signature, message, &tree, &parents)
+        .map_err(git_error("Failed to commit"))?;
+    let short = repo
+        .find_object(id, None)
+        .and_then(|commit| commit.short_id())
+        .map_err(git_error("Failed to read the new commit"))?;
+    Ok(short.as_str().unwrap_or_default().to_string())
+}
+
+/// The tree of `head` with `paths` as they are in `index`
+fn commit_tree<'r>(
+    repo: &'r Repository,
+    head: Option<&Commit>,
+    index: &Index,
+    paths: &[PathBuf],
+) -> Result<git2::Tree<'r>, git2::Error> {
+    let mut tree_index = Index::new()?;
+    if let Some(head) = head {
+        tree_index.read_tree(&head.tree()?)?;
+    }
+    for path in paths {
+        match index.get_path(path, 0) {
+            Some(entry) => tree_index.add(&entry)?,
+            None => tree_index.remove_path(path)?,
+        }
+    }
+    repo.find_tree(tree_index.write_tree_to(repo)?)
+}
+
+/// The work tree of `repo`, canonicalized so paths inside it can be made relative to it
+fn work_tree(repo: &Repository, project: &Path) -> Result<PathBuf> {
+    let Some(workdir) = repo.workdir() else {
+        return Err(GuidSyncError::NoGitWorkTree(project.to_path_buf()));
+    };
+    workdir.canonicalize().io_context(|| format!("Failed to resolve {}", workdir.display()))
+}
+
+/// `path` relative to the work tree, as git2 wants it
+fn relative_to(workdir: &Path, path: &Path) -> Result<PathBuf> {
+    // A deleted file cannot be resolved, its folder still can
+    let resolved = match (path.canonicalize(), path.parent(), path.file_name()) {
+        (Ok(resolved), _, _) => resolved,
+        (Err(_), Some(parent), Some(name)) => parent
+            .canonicalize()
+            .io_context(|| format!("Failed to resolve {}", path.display()))?
+            .join(name),
+        (Err(e), _, _) => return Err(e).io_context(|| format!("Failed to resolve {}", path.display())),
+    };
+    resolved.strip_prefix(workdir).map(Path::to_path_buf).map_err(|_| GuidSyncError::OutsideGitWorkTree {
+        path: path.to_path_buf(),
+        work_tree: workdir.to_path_buf(),
+    })
+}
+
+/// Attach `message` to a libgit2 error
+fn git_error(message: impl Into<String>) -> impl FnOnce(git2::Error) -> GuidSyncError {
+    let message = message.into();
+    move |source| GuidSyncError::Git { message, source }
+}
+
+/// The two letter code `git status --porcelain` shows for a file
+fn status_code(status: Status) -> String {
+    if status.contains(Status::WT_NEW) {
+        return "??".to_string();
+    }
+    let staged = if status.contains(Status::INDEX_NEW) {
+        'A'
+    } else if status.contains(Status::INDEX_MODIFIED) {
+        'M'
+    } else if status.contains(Status::INDEX_DELETED) {
+        'D'
+    } else if status.contains(Status::INDEX_RENAMED) {
+        'R'
+    } else if status.contains(Status::INDEX_TYPECHANGE) {
+        'T'
+    } else {
+        ' '
+    };
+    let unstaged = if status.contains(Status::WT_MODIFIED) {
+        'M'
+    } else if status.contains(Status::WT_DELETED) {
+        'D'
+    } else if status.contains(Status::WT_RENAMED) {
+        'R'
+    } else if status.contains(Status::WT_TYPECHANGE) {
+        'T'
+    } else {
+        ' '
+    };
+    format!("{}{}", staged, unstaged)
+}
+
+/// Files under `dir` that changed since the commit `since` names: committed, staged or only in
+/// the work tree, plus new untracked files. Deleted files are listed too.
+pub fn changed_since(dir: &Path, since: &str) -> Result<Vec<PathBuf>> {
+    let Ok(repo) = Repository::discover(dir) else {
+        return Err(GuidSyncError::NotInGitRepository { path: dir.to_path_buf(), flag: "--since" });
+    };
+    let Ok(commit) = repo.revparse_single(since).and_then(|object| object.peel_to_commit()) else {
+        return Err(GuidSyncError::NotAGitCommit { rev: since.to_string(), path: dir.to_path_buf() });
+    };
+    let workdir = work_tree(&repo, dir)?;
+
+    // Renames are not detected, so a moved file shows up at both its old and new path
+    let mut options = DiffOptions::new();
+    options.include_untracked(true).recurse_untracked_dirs(true);
+    let inside = relative_to(&workdir, dir)?;
+    if !inside.as_os_str().is_empty() {
+        options.pathspec(&inside);
+    }
+    let diff = commit
+        .tree()
+        .and_then(|tree| repo.diff_tree_to_workdir_with_index(Some(&tree), Some(&mut options)))
+        .map_err(git_error(format!("Failed to diff {} against {}", dir.display(), since)))?;
+    let mut changed: Vec<PathBuf> = diff
+        .deltas()
+        .flat_map(|delta| [delta.old_file().path(), delta.new_file().path()])
+        .flatten()
+        .map(|path| workdir.join(path))
+        .collect();
+    changed.sort();
+    changed.dedup();
+    Ok(changed)
+}
@@ -1,50 +1,269 @@
-use anyhow::{Result, Context};
-use colored::*;
-use regex::Regex;
+use aho_corasick::AhoCorasick;
+use rayon::prelude::*;
+use regex::{Captures, Regex};
 use serde::{Deserialize, Serialize};
-use std::collections::{HashMap, HashSet};
+use sha2::{Digest, Sha256};
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fs;
-use std::io::{BufRead, BufReader};
+use std::io::{BufRead, BufReader, Read};
 use std::path::{Path, PathBuf};
+use std::sync::{Arc, LazyLock, Mutex};
+use tracing::{debug, info, warn};
 use walkdir::WalkDir;
 
-use crate::meta_parser::MetaFile;
+use crate::asset_database::{AssetDatabase, MainGuidSource};
+use crate::atomic_write::write_atomic;
+use crate::error::{error_chain, GuidSyncError, IoContext, Result};
+use crate::checkpoint::{Checkpoint, CheckpointStep};
+use crate::diff::unified_diff;
+use crate::editor_settings::SerializationMode;
+use crate::mapping_file::{GuidPair, PlanOptions};
+use crate::meta_parser::{MetaFile, MetaInfo};
+use crate::observer::{NoObserver, ReferencePass, SyncObserver};
+use crate::path_filter::PathFilter;
+use crate::scan_cache::{CachedMeta, ScanCache};
+use crate::slash_path;
+use crate::text_encoding::TextEncoding;
+
+/// Matches a `guid:` reference, including those inside `{fileID: ..., guid: ..., type: ...}`.
+/// Any spacing around the colon is accepted, including line breaks inside a flow mapping, and
+/// the GUID may be wrapped in quotes; the prefix and quotes are captured so they can be kept.
+/// The key is case-insensitive so the `"GUID:..."` references in assembly definitions match too.
+/// A `type:` right after the GUID is captured as well, so it can be rewritten with it. The GUID
+/// must end at a word boundary, so a longer hex value is not taken for one.
+static GUID_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"(?<prefix>\b(?i:guid)\s*:\s*)(?<open>['"]?)(?<guid>[a-fA-F0-9]{32})\b(?<close>['"]?)(?:(?<type_key>\s*,\s*type\s*:\s*)(?<type>\d+))?"#)
+        .expect("valid GUID regex")
+});
+
+/// The GUIDs a pass is looking for, to skip files that mention none of them before running
+/// `GUID_REGEX` over them. Most files of a large project reference none of the changed GUIDs,
+/// and one substring search is much cheaper than capturing every reference. Case is ignored,
+/// as it is by the regex.
+struct GuidPrefilter(Option<AhoCorasick>);
+
+impl GuidPrefilter {
+    fn new<'a>(guids: impl IntoIterator<Item = &'a str>) -> Self {
+        let searcher = AhoCorasick::builder().ascii_case_insensitive(true).build(guids);
+        if let Err(e) = &searcher {
+            debug!("Scanning every file, could not build the GUID prefilter: {}", e);
+        }
+        Self(searcher.ok())
+    }
+
+    /// False only if `content` certainly contains none of the GUIDs
+    fn may_reference(&self, content: &str) -> bool {
+        self.0.as_ref().is_none_or(|searcher| searcher.is_match(content))
+    }
+}
 
 #[derive(Debug, Default, Serialize, Deserialize)]
 pub struct SyncReport {
     meta_files_changed: usize,
-    files_with_references: HashSet<PathBuf>,
-    total_references_replaced: usize,
+    // relative_path -> references replaced, the totals are derived from it; reports from
+    // before the counts load with 0, and older ones have full paths
+    #[serde(serialize_with = "slash_path::serialize_counts", deserialize_with = "slash_path::deserialize_counts")]
+    files_with_references: HashMap<PathBuf, usize>,
     guid_reference_counts: HashMap<String, usize>,
+    backed_up_files: HashMap<PathBuf, PathBuf>, // original -> backup
+    guid_changes: Vec<GuidChange>,
+    #[serde(default)]
+    skipped_files: Vec<(PathBuf, String)>, // path -> reason it could not be read or parsed
+    #[serde(default)]
+    failed_files: Vec<(PathBuf, String)>, // path -> reason it could not be written, left unchanged
+    #[serde(skip)]
+    diffs: Vec<(PathBuf, String)>, // relative_path -> unified diff, only collected for --show-diff
+    #[serde(default)]
+    total_bytes_before: u64, // combined size of the modified files before the sync
+    #[serde(default)]
+    total_bytes_after: u64,
+    #[serde(default, serialize_with = "slash_path::serialize_set")]
+    touched_directories: HashSet<PathBuf>, // relative directories containing a modified file
+    #[serde(default)]
+    binary_files: usize, // reference files stored as binary, which cannot be rewritten
+}
+
+/// A single meta file GUID rewrite, recorded so a sync can be reversed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GuidChange {
+    pub meta_path: PathBuf,
+    pub old_guid: String,
+    pub new_guid: String,
+    /// Which project's GUID was kept for this asset
+    #[serde(default)]
+    pub direction: SyncDirection,
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct SyncOptions {
+    pub dry_run: bool,
+    /// Directory to copy each file into before it is modified
+    pub backup_dir: Option<PathBuf>,
+    /// Allow writing into an already existing backup directory
+    pub force: bool,
+    /// Maximum number of threads for the reference update walk (None = one per core)
+    pub jobs: Option<usize>,
+    /// Proceed even if a target GUID is already used by another subordinate asset
+    pub allow_collisions: bool,
+    /// On a dry run, print a unified diff of every line that would change
+    pub show_diff: bool,
+    /// Maximum number of diff lines printed with `show_diff`
+    pub max_diff_lines: usize,
+    /// Also rewrite references embedded in meta files (importer settings, sprite tags, ...),
+    /// never a meta file's own top-level `guid:` line
+    pub rewrite_meta_refs: bool,
+    /// Number of most referenced GUIDs in the dry run report, 0 for all of them
+    pub top: usize,
+    /// On a dry run, write the new contents of every file that would change into this
+    /// directory, mirroring the paths relative to the subordinate project
+    pub shadow_dir: Option<PathBuf>,
+    /// On a live sync, record every finished file in this checkpoint, skipping the files an
+    /// existing one lists as done, and delete it once the sync completes
+    pub checkpoint: Option<PathBuf>,
+    /// Only rewrite the meta files' own GUIDs, leaving references for a later pass
+    pub meta_only: bool,
+    /// Only rewrite references, leaving the meta files' GUIDs as they are
+    pub refs_only: bool,
+    /// Only rewrite the references in this one file of the subordinate project, leaving the
+    /// meta files and every other file as they are
+    pub only_file: Option<PathBuf>,
+    /// How to sync a subordinate GUID whose meta files map to different main GUIDs. None
+    /// refuses to sync while there are any, see `GuidSyncer::ambiguous_guids`.
+    pub resolve_ambiguous: Option<AmbiguousResolution>,
+}
+
+/// What a sync does with an ambiguous subordinate GUID
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum AmbiguousResolution {
+    /// Rewrite the references to the main GUID of the first meta file in path order
+    First,
+    /// Leave the meta files sharing the GUID, and every reference to it, unchanged
+    Skip,
+}
+
+/// Unity's built-in resources are referenced by GUIDs starting with this and have no meta file
+const BUILTIN_GUID_PREFIX: &str = "0000000000000000";
+
+/// Default for `SyncOptions::top` and the `--top` options
+pub const DEFAULT_TOP: usize = 10;
+
+/// How many entries a `top` setting lets through: 0 means all of them
+pub fn top_limit(top: usize) -> usize {
+    if top == 0 { usize::MAX } else { top }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct SyncOperation {
     pub old_guid: String,
     pub new_guid: String,
+    #[serde(serialize_with = "slash_path::serialize")]
     pub asset_path: PathBuf,
+    /// File name of the asset including its own extension, e.g. `sprite.9.png`
     pub asset_name: String,
+    /// Importer block of the asset's meta file, e.g. `TextureImporter`; empty if it has none
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub asset_importer: String,
     pub meta_file_update: MetaFileUpdate,
     pub reference_updates: Vec<ReferenceUpdate>,
     pub total_references: usize,
+    /// How much could break if this change goes wrong, see `RiskLevel::assess`
+    #[serde(default)]
+    pub risk: RiskLevel,
+    /// References to the asset's main GUID within the main project, with `--scan-main-refs`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub main_reference_count: Option<usize>,
+}
+
+/// Rough impact of a single GUID change, used to review the riskiest changes first
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RiskLevel {
+    #[default]
+    Low,
+    Medium,
+    High,
+}
+
+impl RiskLevel {
+    /// Score each reference by the kind of file it is in and bucket the total: scenes weigh 5,
+    /// prefabs 3, animation and other assets 2, materials and everything else 1. A score of 20
+    /// or more is high risk, 5 or more medium, anything below low.
+    pub fn assess(references: &[ReferenceUpdate]) -> Self {
+        let score: usize = references
+            .iter()
+            .map(|r| file_type_weight(&r.file_type) * r.reference_count)
+            .sum();
+        match score {
+            20.. => RiskLevel::High,
+            5.. => RiskLevel::Medium,
+            _ => RiskLevel::Low,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RiskLevel::Low => "low",
+            RiskLevel::Medium => "medium",
+            RiskLevel::High => "high",
+        }
+    }
+}
+
+fn file_type_weight(file_type: &str) -> usize {
+    match file_type {
+        "unity" => 5,
+        "prefab" => 3,
+        "asset" | "controller" | "overrideController" | "anim" | "playable" => 2,
+        _ => 1,
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct MetaFileUpdate {
+    #[serde(serialize_with = "slash_path::serialize")]
     pub path: PathBuf,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ReferenceUpdate {
+    #[serde(serialize_with = "slash_path::serialize")]
     pub file_path: PathBuf,
     pub file_type: String,
     pub reference_count: usize,
+    /// Individual references, only collected when the report is generated with context
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub references: Option<Vec<ReferenceDetail>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReferenceDetail {
+    pub line_number: usize,
+    pub context_snippet: String,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct SyncOperationsReport {
     pub summary: SyncSummary,
     pub operations: Vec<SyncOperation>,
+    /// Files that could not be read or parsed (path, reason), and so were not looked at
+    #[serde(default)]
+    pub skipped_files: Vec<(PathBuf, String)>,
+    /// GUIDs the subordinate references that no meta file has, with `--report-dangling`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub dangling_references: Vec<DanglingReference>,
+    /// Options of the sync, stored in a `--plan` so `apply` replays them
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub options: Option<PlanOptions>,
+}
+
+/// A GUID referenced in the subordinate project that no meta file in either project has, the
+/// kind of broken reference Unity shows as a missing script or a pink material
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DanglingReference {
+    pub guid: String,
+    /// Each subordinate file referencing the GUID, relative to Assets, with its reference count
+    pub files: Vec<(PathBuf, usize)>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -55,42 +274,196 @@ pub struct SyncSummary {
     pub total_reference_updates: usize,
 }
 
+impl SyncSummary {
+    fn of(operations: &[SyncOperation]) -> Self {
+        let files_with_references: HashSet<&PathBuf> = operations
+            .iter()
+            .flat_map(|op| op.reference_updates.iter().map(|r| &r.file_path))
+            .collect();
+        Self {
+            total_guid_differences: operations.len(),
+            total_meta_files_to_update: operations.len(),
+            total_files_with_references: files_with_references.len(),
+            total_reference_updates: operations.iter().map(|op| op.total_references).sum(),
+        }
+    }
+}
+
+/// Reports for a sync that rewrote several projects, keyed by project path
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct CombinedSyncReport {
+    pub subordinates: BTreeMap<PathBuf, SyncReport>,
+}
+
+impl CombinedSyncReport {
+    pub fn add(&mut self, subordinate: PathBuf, report: SyncReport) {
+        self.subordinates.insert(subordinate, report);
+    }
+
+    pub fn export_to_file(&self, path: &Path) -> Result<()> {
+        write_report_json(self, path)
+    }
+
+    /// `SyncReport::summary_line` over all the projects
+    pub fn total_line(&self) -> String {
+        let mut total = SyncReport::new();
+        for (subordinate, report) in &self.subordinates {
+            total.meta_files_changed += report.meta_files_changed;
+            // Relative paths of different projects may be the same, so they are kept apart
+            total
+                .files_with_references
+                .extend(report.files_with_references.iter().map(|(path, count)| (subordinate.join(path), *count)));
+        }
+        total.summary_line()
+    }
+}
+
 impl SyncReport {
     fn new() -> Self {
         Self::default()
     }
 
+    pub fn summary_line(&self) -> String {
+        format!(
+            "{} meta files, {} references in {} files",
+            self.meta_files_changed,
+            self.total_references_replaced(),
+            self.files_with_references.len()
+        )
+    }
+
     pub fn export_to_file(&self, path: &Path) -> Result<()> {
-        let json = serde_json::to_string_pretty(self)?;
-        fs::write(path, json)?;
-        Ok(())
+        write_report_json(self, path)
     }
 
-    pub fn print(&self) {
-        println!("\n{}", "═══════════════════════════════════════".bright_white());
-        println!("{}", "       DRY RUN REPORT SUMMARY".bright_white().bold());
-        println!("{}", "═══════════════════════════════════════".bright_white());
-        
-        println!("\n{}", "📁 Meta Files to Update:".bright_cyan().bold());
-        println!("   {} meta files will have their GUIDs changed", self.meta_files_changed);
-        
-        println!("\n{}", "🔗 Reference Updates:".bright_cyan().bold());
-        println!("   {} total GUID references will be updated", self.total_references_replaced);
-        println!("   {} files contain references that need updating", self.files_with_references.len());
-        
-        if !self.guid_reference_counts.is_empty() {
-            println!("\n{}", "📊 Top Referenced GUIDs:".bright_cyan().bold());
-            let mut counts: Vec<_> = self.guid_reference_counts.iter().collect();
-            counts.sort_by(|a, b| b.1.cmp(a.1));
-            for (guid, count) in counts.iter().take(10) {
-                println!("   {} - {} references", guid.bright_yellow(), count);
-            }
+    pub fn guid_changes(&self) -> &[GuidChange] {
+        &self.guid_changes
+    }
+
+    /// The files whose references were rewritten, relative to the project's Assets folder, with
+    /// the number rewritten in each
+    pub fn files_with_references(&self) -> &HashMap<PathBuf, usize> {
+        &self.files_with_references
+    }
+
+    pub fn total_references_replaced(&self) -> usize {
+        self.files_with_references.values().sum()
+    }
+
+    /// Files that could not be read or parsed (path, reason)
+    pub fn skipped_files(&self) -> &[(PathBuf, String)] {
+        &self.skipped_files
+    }
+
+    /// Files that could not be written (path, reason) and were left unchanged
+    pub fn failed_files(&self) -> &[(PathBuf, String)] {
+        &self.failed_files
+    }
+
+    pub fn meta_files_changed(&self) -> usize {
+        self.meta_files_changed
+    }
+
+    /// Number of references rewritten per lowercase GUID
+    pub fn guid_reference_counts(&self) -> &HashMap<String, usize> {
+        &self.guid_reference_counts
+    }
+
+    /// Reference files stored as binary, which were skipped because they cannot be rewritten
+    pub fn binary_files(&self) -> usize {
+        self.binary_files
+    }
+
+    /// Unified diff of every file the sync rewrote, or would have in a dry run, as
+    /// (relative path, diff). Only collected with `SyncOptions::show_diff`.
+    pub fn diffs(&self) -> &[(PathBuf, String)] {
+        &self.diffs
+    }
+
+    /// Original -> backup path of every file copied to the backup directory
+    pub fn backed_up_files(&self) -> &HashMap<PathBuf, PathBuf> {
+        &self.backed_up_files
+    }
+
+    /// Fold another (partial) report into this one
+    fn merge(&mut self, other: SyncReport) {
+        self.meta_files_changed += other.meta_files_changed;
+        for (path, count) in other.files_with_references {
+            *self.files_with_references.entry(path).or_insert(0) += count;
         }
-        
-        println!("\n{}", "═══════════════════════════════════════".bright_white());
-        println!("{}", "To apply these changes, run without --dry-run flag".bright_green());
-        println!("{}", "WARNING: This will modify files in the subordinate project!".bright_red().bold());
-        println!("{}", "═══════════════════════════════════════".bright_white());
+        for (guid, count) in other.guid_reference_counts {
+            *self.guid_reference_counts.entry(guid).or_insert(0) += count;
+        }
+        self.backed_up_files.extend(other.backed_up_files);
+        self.guid_changes.extend(other.guid_changes);
+        self.skipped_files.extend(other.skipped_files);
+        self.failed_files.extend(other.failed_files);
+        self.diffs.extend(other.diffs);
+        self.total_bytes_before += other.total_bytes_before;
+        self.total_bytes_after += other.total_bytes_after;
+        self.touched_directories.extend(other.touched_directories);
+        self.binary_files += other.binary_files;
+    }
+
+    /// Account for a modified file: its size before and after, and the directory it is in
+    fn record_size(&mut self, relative_path: &Path, before: usize, after: usize) {
+        self.total_bytes_before += before as u64;
+        self.total_bytes_after += after as u64;
+        let directory = match relative_path.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent.to_path_buf(),
+            _ => PathBuf::from("."),
+        };
+        self.touched_directories.insert(directory);
+    }
+
+    /// Size of the modified files before and after, e.g. `1200 -> 1200 bytes (+0) in 3 directories`
+    pub fn size_line(&self) -> String {
+        format!(
+            "{} -> {} bytes ({:+}) in {} directories",
+            self.total_bytes_before,
+            self.total_bytes_after,
+            self.total_bytes_after as i64 - self.total_bytes_before as i64,
+            self.touched_directories.len()
+        )
+    }
+
+    pub fn load_from_file(path: &Path) -> Result<Self> {
+        let json = fs::read_to_string(path)
+            .io_context(|| format!("Failed to read sync report: {}", path.display()))?;
+        let report = serde_json::from_str(&json).map_err(|source| GuidSyncError::Json {
+            message: format!("Failed to parse sync report: {}", path.display()),
+            source,
+        })?;
+        Ok(report)
+    }
+
+    /// Load the report of `project` from a file written by `sync --report`. That is either a
+    /// single project's report, or the combined report of a sync that rewrote several projects,
+    /// from which the entry for `project` is picked.
+    pub fn load_for_project(path: &Path, project: &Path) -> Result<Self> {
+        let json = fs::read_to_string(path)
+            .io_context(|| format!("Failed to read sync report: {}", path.display()))?;
+        let parse_error = |source| GuidSyncError::Json {
+            message: format!("Failed to parse sync report: {}", path.display()),
+            source,
+        };
+        let value: serde_json::Value = serde_json::from_str(&json).map_err(parse_error)?;
+        if value.get("subordinates").is_none() {
+            return serde_json::from_value(value).map_err(parse_error);
+        }
+
+        let combined: CombinedSyncReport = serde_json::from_value(value).map_err(parse_error)?;
+        let projects: Vec<PathBuf> = combined.subordinates.keys().cloned().collect();
+        combined
+            .subordinates
+            .into_iter()
+            .find(|(reported, _)| same_project(reported, project))
+            .map(|(_, report)| report)
+            .ok_or_else(|| GuidSyncError::ProjectNotInReport {
+                path: path.to_path_buf(),
+                project: project.to_path_buf(),
+                projects,
+            })
     }
 }
 
@@ -98,6 +471,199 @@ pub struct GuidSyncer {
     main_project: PathBuf,
     subordinate_project: PathBuf,
     guid_mappings: HashMap<PathBuf, (String, String)>, // relative_path -> (main_guid, sub_guid)
+    collisions: Vec<GuidCollision>,
+    scan_options: ScanOptions,
+    reference_only_mappings: HashMap<String, String>, // sub_guid -> main_guid, for GUIDs without a subordinate meta
+    paired_paths: HashMap<PathBuf, PathBuf>, // sub relative_path -> main relative_path, for pairs not at the same path
+    orphaned_metas: Vec<PathBuf>, // meta files without an asset file, seen while matching by content
+    main_only: Vec<PathBuf>, // main meta files without a subordinate counterpart, see main_only
+    sub_only: Vec<PathBuf>, // subordinate meta files without a main counterpart, see sub_only
+    direction: SyncDirection, // recorded on every GuidChange this syncer makes
+    skipped_files: Mutex<Vec<(PathBuf, String)>>, // unreadable meta files seen while scanning
+    scan_cache: Option<ScanCache>, // main project scan cache, see load_cache
+    type_overrides: HashMap<String, u32>, // sub_guid -> new `type:` of its references, from a mapping file
+    observer: Arc<dyn SyncObserver>, // told about scan and sync progress, see with_observer
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct ScanOptions {
+    pub match_by: MatchMode,
+    /// Restricts which assets are scanned and which files have their references updated
+    pub filter: PathFilter,
+    /// Leave folder meta files (`folderAsset: yes`) out of the scan entirely
+    pub skip_folders: bool,
+    /// Also scan embedded packages in the project's `Packages` folder next to `Assets`
+    pub include_packages: bool,
+    /// Also update references in the `ProjectSettings/*.asset` files next to `Assets`. They have
+    /// no meta files, so they never take part in the GUID comparison.
+    pub include_project_settings: bool,
+    /// Error out instead of skipping files that cannot be read or parsed
+    pub fail_on_warning: bool,
+    /// Extensions of the files whose references are counted and rewritten, without the dot.
+    /// Empty means `DEFAULT_REFERENCE_EXTENSIONS`.
+    pub reference_extensions: Vec<String>,
+    /// Compare the subordinate's meta files as they are read instead of collecting them first
+    pub low_memory: bool,
+    /// Subordinate meta path -> main meta path, for assets that moved between the projects.
+    /// Only consulted when the main project has nothing at the subordinate's path.
+    pub path_map: HashMap<PathBuf, PathBuf>,
+    /// Descend into symlinked folders, visiting each real file only once
+    pub follow_symlinks: bool,
+    /// Read reference files that are not UTF-8 as UTF-16 or Latin-1, and write them back that way
+    pub try_encodings: bool,
+    /// Only keep the differences whose main or subordinate GUID starts with this lowercase hex
+    pub guid_prefix: Option<String>,
+    /// With `Db`, warn about main meta files whose GUID Unity's imported asset database lacks
+    pub main_guid_source: MainGuidSource,
+}
+
+impl ScanOptions {
+    /// Whether a file's extension is one whose references are counted and rewritten
+    pub fn is_reference_file(&self, path: &Path) -> bool {
+        let Some(ext) = path.extension().and_then(|s| s.to_str()) else {
+            return false;
+        };
+        if self.reference_extensions.is_empty() {
+            DEFAULT_REFERENCE_EXTENSIONS.iter().any(|known| known.eq_ignore_ascii_case(ext))
+        } else {
+            self.reference_extensions
+                .iter()
+                .any(|known| known.trim_start_matches('.').eq_ignore_ascii_case(ext))
+        }
+    }
+}
+
+/// Extensions of the Unity files that can reference other assets by GUID
+pub const DEFAULT_REFERENCE_EXTENSIONS: &[&str] = &[
+    "unity",
+    "prefab",
+    "asset",
+    "mat",
+    "controller",
+    "overrideController",
+    "anim",
+    "playable",
+    "spriteatlas",
+    "asmdef",
+    "asmref",
+    "shadervariants",
+    "mask",
+    "mixer",
+    "physicMaterial",
+    "renderTexture",
+    "lighting",
+    "terrainlayer",
+];
+
+/// A meta file whose GUID differs between the two projects
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GuidDifference {
+    pub relative_path: PathBuf,
+    pub main_guid: String,
+    pub sub_guid: String,
+    /// The main project meta file this one was paired with when it is not at the same path,
+    /// by content or through the path map
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub paired_with: Option<PathBuf>,
+}
+
+/// Machine-readable result of a scan
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ScanResult {
+    pub differences: Vec<GuidDifference>,
+    pub collisions: Vec<GuidCollision>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub ambiguous: Vec<AmbiguousGuid>,
+    /// Meta files whose asset file is missing, found while matching by content
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub orphaned_metas: Vec<PathBuf>,
+    /// Main meta files that could not be paired with any subordinate meta file
+    #[serde(default)]
+    pub main_only: Vec<PathBuf>,
+    /// Subordinate meta files that could not be paired with any main meta file
+    #[serde(default)]
+    pub sub_only: Vec<PathBuf>,
+}
+
+/// How assets are paired between the main and subordinate projects
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum MatchMode {
+    /// Pair assets by identical relative path only
+    #[default]
+    Path,
+    /// Also pair otherwise unmatched assets by the hash of their contents
+    Content,
+}
+
+/// Which project keeps its GUID when the two projects disagree
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+#[allow(clippy::enum_variant_names)] // variant names mirror the --direction values
+pub enum SyncDirection {
+    /// The main project's GUID is kept and the subordinate is rewritten
+    #[default]
+    MainWins,
+    /// The subordinate project's GUID is kept and the main project is rewritten
+    SubWins,
+    /// The GUID of whichever meta file was modified most recently is kept
+    NewestWins,
+}
+
+/// A reference that still points at a GUID that should have been rewritten
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StaleReference {
+    pub file_path: PathBuf,
+    pub line_number: usize,
+    pub guid: String,
+    pub expected_guid: String,
+}
+
+/// A mapping whose target GUID is already used by a different asset in the subordinate project
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GuidCollision {
+    pub guid: String,
+    pub asset_path: PathBuf,
+    pub existing_path: PathBuf,
+}
+
+/// A subordinate GUID shared by meta files that map to different main GUIDs, so references
+/// to it could be rewritten to either
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AmbiguousGuid {
+    pub sub_guid: String,
+    /// Each subordinate meta path sharing the GUID with the main GUID it maps to, by path
+    pub candidates: Vec<(PathBuf, String)>,
+}
+
+/// Number of most referenced GUIDs listed in `ProjectStats`
+const TOP_REFERENCED_GUIDS: usize = 10;
+
+/// Census of a single project, see `GuidSyncer::project_stats`
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ProjectStats {
+    pub meta_files: usize,
+    pub distinct_guids: usize,
+    pub folder_assets: usize,
+    pub file_assets: usize,
+    /// Files references are looked for in (Unity YAML and assembly definitions)
+    pub reference_files: usize,
+    /// Most referenced GUIDs first
+    pub top_referenced: Vec<ReferencedGuid>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ReferencedGuid {
+    pub guid: String,
+    pub references: usize,
+    /// The meta file carrying the GUID, if it is in the project
+    pub meta_path: Option<PathBuf>,
+}
+
+/// A GUID carried by more than one meta file within the same project
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DuplicateGuid {
+    pub guid: String,
+    pub meta_paths: Vec<PathBuf>,
 }
 
 impl GuidSyncer {
@@ -106,292 +672,1529 @@ impl GuidSyncer {
             main_project,
             subordinate_project,
             guid_mappings: HashMap::new(),
+            collisions: Vec::new(),
+            scan_options: ScanOptions::default(),
+            reference_only_mappings: HashMap::new(),
+            paired_paths: HashMap::new(),
+            orphaned_metas: Vec::new(),
+            main_only: Vec::new(),
+            sub_only: Vec::new(),
+            direction: SyncDirection::MainWins,
+            skipped_files: Mutex::new(Vec::new()),
+            scan_cache: None,
+            type_overrides: HashMap::new(),
+            observer: Arc::new(NoObserver),
+        }
+    }
+
+    pub fn with_scan_options(mut self, scan_options: ScanOptions) -> Self {
+        self.scan_options = scan_options;
+        self
+    }
+
+    /// Report scan and sync progress to `observer` as well as the log
+    pub fn with_observer(mut self, observer: Arc<dyn SyncObserver>) -> Self {
+        self.observer = observer;
+        self
+    }
+    
+    /// Build a syncer that reverses the GUID changes recorded in a previous sync report
+    pub fn from_sync_report(subordinate_project: PathBuf, report: &SyncReport) -> Self {
+        let guid_mappings = report
+            .guid_changes
+            .iter()
+            .map(|change| {
+                (
+                    change.meta_path.clone(),
+                    (change.old_guid.clone(), change.new_guid.clone()),
+                )
+            })
+            .collect();
+
+        Self {
+            guid_mappings,
+            ..Self::new(PathBuf::new(), subordinate_project)
+        }
+    }
+    
+    /// Build a syncer from explicit old -> new GUID pairs instead of comparing two projects.
+    /// Subordinate meta files carrying an old GUID are rewritten along with their references;
+    /// old GUIDs without a meta file are still rewritten wherever they are referenced. The
+    /// subordinate is scanned, and later synced, with `scan_options`.
+    pub fn from_guid_pairs(subordinate_project: PathBuf, pairs: &[GuidPair], scan_options: ScanOptions) -> Result<Self> {
+        Self::new(PathBuf::new(), subordinate_project).with_scan_options(scan_options).with_guid_pairs(pairs)
+    }
+
+    /// `from_guid_pairs` for a syncer built with `new(PathBuf::new(), subordinate)`, so the scan
+    /// of the subordinate already reports to its observer
+    pub fn with_guid_pairs(mut self, pairs: &[GuidPair]) -> Result<Self> {
+        let sub_metas = self.scan_meta_files(&self.subordinate_project)?;
+
+        let mut reference_only: HashMap<String, String> = pairs
+            .iter()
+            .map(|pair| (pair.old_guid.clone(), pair.new_guid.clone()))
+            .collect();
+        for (rel_path, sub_guid) in &sub_metas {
+            if let Some(new_guid) = reference_only.get(sub_guid) {
+                self.guid_mappings.insert(rel_path.clone(), (new_guid.clone(), sub_guid.clone()));
+            }
+        }
+        for (_, sub_guid) in self.guid_mappings.values() {
+            reference_only.remove(sub_guid);
+        }
+
+        self.reference_only_mappings = reference_only;
+        self.type_overrides = pairs
+            .iter()
+            .filter_map(|pair| Some((pair.old_guid.clone(), pair.new_type?)))
+            .collect();
+        self.collisions = self.find_collisions(&sub_metas);
+        Ok(self)
+    }
+    
+    pub fn get_difference_count(&self) -> usize {
+        self.guid_mappings.len()
+    }
+
+    /// Number of GUIDs that are only rewritten in references, because no meta file carries them
+    pub fn get_reference_only_count(&self) -> usize {
+        self.reference_only_mappings.len()
+    }
+
+    pub fn scan_projects(&mut self) -> Result<()> {
+        let main_metas = self.scan_main_project()?;
+        self.scan_projects_with_main(&main_metas)
+    }
+
+    /// Scan only the main project's meta files (relative_path -> guid), so a single scan
+    /// can be shared between several subordinate projects
+    pub fn scan_main_project(&mut self) -> Result<HashMap<PathBuf, String>> {
+        let mut cache = self.scan_cache.take();
+        let main_metas = self.scan_meta_files_with_cache(&self.main_project, cache.as_mut())?;
+        self.scan_cache = cache;
+        if self.scan_options.main_guid_source == MainGuidSource::Db {
+            self.check_asset_database(&main_metas)?;
+        }
+        Ok(main_metas)
+    }
+
+    /// Warn about main meta files whose GUID is missing from the project's imported asset
+    /// database: the meta changed since Unity last imported it, so it may not be authoritative
+    fn check_asset_database(&self, main_metas: &HashMap<PathBuf, String>) -> Result<()> {
+        let Some(database) = AssetDatabase::open(&self.main_project)? else {
+            return Ok(());
+        };
+        let mut stale: Vec<(&PathBuf, &String)> =
+            main_metas.iter().filter(|(_, guid)| !database.contains(guid)).collect();
+        stale.sort();
+        for (rel_path, guid) in &stale {
+            warn!("{}: GUID {} is not in the main project's asset database", rel_path.display(), guid);
+        }
+        if stale.is_empty() {
+            info!("All {} main meta files match the asset database ({} GUIDs)", main_metas.len(), database.len());
+        } else {
+            warn!(
+                "{} of {} main meta files disagree with the imported asset database; open the main project in Unity to reimport it before syncing",
+                stale.len(),
+                main_metas.len()
+            );
+        }
+        Ok(())
+    }
+
+    /// Reuse a main project scan saved by `save_cache`: only meta files modified since then
+    /// are read again. A missing file or a cache for another project starts out empty.
+    pub fn load_cache(&mut self, path: &Path) -> Result<()> {
+        self.scan_cache = Some(ScanCache::load(path, &self.main_project)?);
+        Ok(())
+    }
+
+    /// Save the main project scan for the next run (only after `load_cache`)
+    pub fn save_cache(&self, path: &Path) -> Result<()> {
+        match &self.scan_cache {
+            Some(cache) => Ok(cache.save(path)?),
+            None => Ok(()),
+        }
+    }
+
+    /// Scan the subordinate project and compare it against an already scanned main project.
+    /// The differences and collisions found are returned by `diff` and `get_collisions`.
+    pub fn scan_projects_with_main(&mut self, main_metas: &HashMap<PathBuf, String>) -> Result<()> {
+        self.check_path_map(main_metas);
+        let (differences, sub_metas) = if self.scan_options.low_memory {
+            if self.scan_options.match_by == MatchMode::Content {
+                return Err(GuidSyncError::LowMemoryContentMatch);
+            }
+            self.stream_subordinate(main_metas)?
+        } else {
+            let sub_metas = self.scan_meta_files(&self.subordinate_project)?;
+            let mut differences: Vec<GuidDifference> = main_metas
+                .iter()
+                .filter_map(|(rel_path, main_guid)| match sub_metas.get(rel_path) {
+                    Some(sub_guid) if sub_guid != main_guid => Some(GuidDifference {
+                        relative_path: rel_path.clone(),
+                        main_guid: main_guid.clone(),
+                        sub_guid: sub_guid.clone(),
+                        paired_with: None,
+                    }),
+                    _ => None,
+                })
+                .collect();
+            for (sub_rel_path, sub_guid) in self.scan_options.path_map.keys().filter_map(|p| sub_metas.get_key_value(p)) {
+                differences.extend(self.path_mapped_difference(main_metas, sub_rel_path, sub_guid));
+            }
+            (differences, sub_metas)
+        };
+        self.check_skipped(self.skipped_files())?;
+
+        for difference in differences {
+            if let Some(main_rel_path) = difference.paired_with {
+                self.paired_paths.insert(difference.relative_path.clone(), main_rel_path);
+            }
+            self.guid_mappings.insert(difference.relative_path, (difference.main_guid, difference.sub_guid));
+        }
+
+        if !self.scan_options.low_memory {
+            let content_pairs = match self.scan_options.match_by {
+                MatchMode::Content => self.match_by_content(main_metas, &sub_metas),
+                MatchMode::Path => Vec::new(),
+            };
+            self.record_unpaired(main_metas, &sub_metas, &content_pairs);
+        }
+
+        // Applied once every matcher has run, so content matches are narrowed down too
+        if let Some(prefix) = &self.scan_options.guid_prefix {
+            self.guid_mappings
+                .retain(|_, (main_guid, sub_guid)| main_guid.starts_with(prefix.as_str()) || sub_guid.starts_with(prefix.as_str()));
+            self.paired_paths.retain(|rel_path, _| self.guid_mappings.contains_key(rel_path));
+        }
+        self.collisions = self.find_collisions(&sub_metas);
+        Ok(())
+    }
+
+    /// Record the meta files found in only one project, after pairing by path, path map and
+    /// `content_pairs` (subordinate path, main path)
+    fn record_unpaired(
+        &mut self,
+        main_metas: &HashMap<PathBuf, String>,
+        sub_metas: &HashMap<PathBuf, String>,
+        content_pairs: &[(PathBuf, PathBuf)],
+    ) {
+        let mut paired_main: HashSet<&PathBuf> = content_pairs.iter().map(|(_, main)| main).collect();
+        let mut paired_sub: HashSet<&PathBuf> = content_pairs.iter().map(|(sub, _)| sub).collect();
+        for (sub_rel_path, main_rel_path) in &self.scan_options.path_map {
+            if sub_metas.contains_key(sub_rel_path)
+                && !main_metas.contains_key(sub_rel_path)
+                && main_metas.contains_key(main_rel_path)
+            {
+                paired_sub.insert(sub_rel_path);
+                paired_main.insert(main_rel_path);
+            }
+        }
+
+        let mut main_only: Vec<PathBuf> = main_metas
+            .keys()
+            .filter(|path| !sub_metas.contains_key(*path) && !paired_main.contains(path))
+            .cloned()
+            .collect();
+        let mut sub_only: Vec<PathBuf> = sub_metas
+            .keys()
+            .filter(|path| !main_metas.contains_key(*path) && !paired_sub.contains(path))
+            .cloned()
+            .collect();
+        main_only.sort();
+        sub_only.sort();
+        self.main_only = main_only;
+        self.sub_only = sub_only;
+    }
+
+    /// Warn about path map entries pointing at a meta file the main project doesn't have
+    fn check_path_map(&self, main_metas: &HashMap<PathBuf, String>) {
+        let mut missing: Vec<&PathBuf> = self
+            .scan_options
+            .path_map
+            .values()
+            .filter(|main_rel_path| !main_metas.contains_key(*main_rel_path))
+            .collect();
+        missing.sort();
+        for main_rel_path in missing {
+            warn!("Path map target does not exist in the main project: {}", main_rel_path.display());
+        }
+    }
+
+    /// The difference between a subordinate meta file and the main meta file the path map
+    /// pairs it with. A main meta file at the same path always takes precedence.
+    fn path_mapped_difference(
+        &self,
+        main_metas: &HashMap<PathBuf, String>,
+        sub_rel_path: &Path,
+        sub_guid: &str,
+    ) -> Option<GuidDifference> {
+        if main_metas.contains_key(sub_rel_path) {
+            return None;
+        }
+        let main_rel_path = self.scan_options.path_map.get(sub_rel_path)?;
+        let main_guid = main_metas.get(main_rel_path)?;
+        (main_guid != sub_guid).then(|| GuidDifference {
+            relative_path: sub_rel_path.to_path_buf(),
+            main_guid: main_guid.clone(),
+            sub_guid: sub_guid.to_string(),
+            paired_with: Some(main_rel_path.clone()),
+        })
+    }
+
+    /// Compare the subordinate's meta files against the main scan as they are read. Instead of
+    /// the full subordinate map, only the unchanged entries whose GUID also occurs in the main
+    /// project are kept, which is all the collision check needs. The unpaired meta files are
+    /// recorded as well.
+    fn stream_subordinate(
+        &mut self,
+        main_metas: &HashMap<PathBuf, String>,
+    ) -> Result<(Vec<GuidDifference>, HashMap<PathBuf, String>)> {
+        let main_guids: HashSet<&str> = main_metas.values().map(String::as_str).collect();
+        let mut differences = Vec::new();
+        let mut collision_candidates = HashMap::new();
+        let mut paired_main = HashSet::new();
+        let mut sub_only = Vec::new();
+
+        self.visit_meta_files(&self.subordinate_project, None, |rel_path, info| {
+            let sub_guid = info.guid;
+            if let Some((main_rel_path, _)) = main_metas.get_key_value(&rel_path) {
+                paired_main.insert(main_rel_path);
+            } else {
+                match self.scan_options.path_map.get_key_value(&rel_path) {
+                    Some((_, main_rel_path)) if main_metas.contains_key(main_rel_path) => {
+                        paired_main.insert(main_rel_path);
+                    }
+                    _ => sub_only.push(rel_path.clone()),
+                }
+            }
+
+            match main_metas.get(&rel_path) {
+                Some(main_guid) if *main_guid != sub_guid => differences.push(GuidDifference {
+                    relative_path: rel_path,
+                    main_guid: main_guid.clone(),
+                    sub_guid,
+                    paired_with: None,
+                }),
+                None if self.scan_options.path_map.contains_key(&rel_path) => {
+                    differences.extend(self.path_mapped_difference(main_metas, &rel_path, &sub_guid));
+                }
+                _ if main_guids.contains(sub_guid.as_str()) => {
+                    collision_candidates.insert(rel_path, sub_guid);
+                }
+                _ => {}
+            }
+        })?;
+
+        let mut main_only: Vec<PathBuf> =
+            main_metas.keys().filter(|path| !paired_main.contains(path)).cloned().collect();
+        main_only.sort();
+        sub_only.sort();
+        self.main_only = main_only;
+        self.sub_only = sub_only;
+        Ok((differences, collision_candidates))
+    }
+
+    /// Pair meta files that have no counterpart at the same relative path by hashing the
+    /// assets they describe. Hashes shared by several files are ambiguous and left unmatched,
+    /// and a pair is only made once the two assets are confirmed to be byte for byte equal.
+    /// Returns every pair made (subordinate path, main path), whether or not the GUIDs differ.
+    fn match_by_content(
+        &mut self,
+        main_metas: &HashMap<PathBuf, String>,
+        sub_metas: &HashMap<PathBuf, String>,
+    ) -> Vec<(PathBuf, PathBuf)> {
+        let mut pairs = Vec::new();
+        let mut orphaned = Vec::new();
+        let main_by_hash = hash_unmatched_assets(&self.main_project, main_metas, sub_metas, &mut orphaned);
+        let sub_by_hash = hash_unmatched_assets(&self.subordinate_project, sub_metas, main_metas, &mut orphaned);
+        orphaned.sort();
+        orphaned.dedup();
+        self.orphaned_metas = orphaned;
+
+        for (hash, sub_paths) in &sub_by_hash {
+            let Some(main_paths) = main_by_hash.get(hash) else {
+                continue;
+            };
+
+            if main_paths.len() != 1 || sub_paths.len() != 1 {
+                warn!(
+                    "{} files share the same content, falling back to path matching: {}",
+                    main_paths.len() + sub_paths.len(),
+                    main_paths
+                        .iter()
+                        .chain(sub_paths)
+                        .map(|p| p.display().to_string())
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                );
+                continue;
+            }
+
+            let (main_path, sub_path) = (&main_paths[0], &sub_paths[0]);
+            if !same_asset_contents(&self.main_project.join(main_path), &self.subordinate_project.join(sub_path)) {
+                warn!(
+                    "{} and {} share a content hash but differ, falling back to path matching",
+                    main_path.display(),
+                    sub_path.display()
+                );
+                continue;
+            }
+            let main_guid = &main_metas[main_path];
+            let sub_guid = &sub_metas[sub_path];
+            if main_guid != sub_guid {
+                // Keyed by the subordinate path, since that is the meta file that gets rewritten
+                self.guid_mappings.insert(sub_path.clone(), (main_guid.clone(), sub_guid.clone()));
+                self.paired_paths.insert(sub_path.clone(), main_path.clone());
+            }
+            pairs.push((sub_path.clone(), main_path.clone()));
+        }
+        pairs
+    }
+
+    /// Decide per asset which project keeps its GUID. Mappings won by the subordinate are
+    /// removed from this syncer and returned as a second syncer that rewrites the main project.
+    pub fn split_by_direction(&mut self, direction: SyncDirection) -> Result<Option<GuidSyncer>> {
+        let mut sub_wins = HashMap::new();
+        for (sub_rel_path, (main_guid, sub_guid)) in &self.guid_mappings {
+            let main_rel_path = self.paired_paths.get(sub_rel_path).unwrap_or(sub_rel_path);
+            let winner = match direction {
+                SyncDirection::NewestWins => {
+                    let main_modified = modified_time(&self.main_project.join(main_rel_path))?;
+                    let sub_modified = modified_time(&self.subordinate_project.join(sub_rel_path))?;
+                    // Ties keep the main project's GUID
+                    if sub_modified > main_modified {
+                        SyncDirection::SubWins
+                    } else {
+                        SyncDirection::MainWins
+                    }
+                }
+                other => other,
+            };
+            if winner == SyncDirection::SubWins {
+                sub_wins.insert(sub_rel_path.clone(), (main_rel_path.clone(), main_guid.clone(), sub_guid.clone()));
+            }
+        }
+        if sub_wins.is_empty() {
+            return Ok(None);
+        }
+
+        let mut reversed = GuidSyncer::new(self.subordinate_project.clone(), self.main_project.clone())
+            .with_scan_options(self.scan_options.clone())
+            .with_observer(Arc::clone(&self.observer));
+        reversed.direction = SyncDirection::SubWins;
+        for (sub_rel_path, (main_rel_path, main_guid, sub_guid)) in sub_wins {
+            self.guid_mappings.remove(&sub_rel_path);
+            reversed.guid_mappings.insert(main_rel_path.clone(), (sub_guid, main_guid));
+            reversed.paired_paths.insert(main_rel_path, sub_rel_path);
+        }
+
+        // Collisions are now checked against the project each half will rewrite
+        let main_metas = self.scan_meta_files(&self.main_project)?;
+        let sub_metas = self.scan_meta_files(&self.subordinate_project)?;
+        reversed.collisions = reversed.find_collisions(&main_metas);
+        self.collisions = self.find_collisions(&sub_metas);
+        Ok(Some(reversed))
+    }
+
+    /// Re-pair only the given meta files (relative paths), replacing any previous mappings.
+    /// Used to react to a few changed files without rescanning both projects.
+    pub fn rescan_assets(&mut self, rel_paths: &[PathBuf]) -> Result<()> {
+        self.guid_mappings.clear();
+        self.reference_only_mappings.clear();
+        self.collisions.clear();
+
+        for rel_path in rel_paths {
+            if !self.scan_options.filter.is_meta_match(rel_path) {
+                continue;
+            }
+            let main_meta = self.main_project.join(rel_path);
+            let sub_meta = self.subordinate_project.join(rel_path);
+            if !main_meta.is_file() || !sub_meta.is_file() {
+                continue;
+            }
+
+            // A meta file may be caught half written, so skip it rather than abort
+            let (main_info, sub_info) = match (MetaFile::parse(&main_meta), MetaFile::parse(&sub_meta)) {
+                (Ok(main_info), Ok(sub_info)) => (main_info, sub_info),
+                (Err(e), _) | (_, Err(e)) => {
+                    warn!("Could not read {}: {}", rel_path.display(), e);
+                    self.record_skipped(rel_path, &e);
+                    continue;
+                }
+            };
+            if self.scan_options.skip_folders && (main_info.is_folder || sub_info.is_folder) {
+                continue;
+            }
+
+            let main_guid = main_info.guid.to_ascii_lowercase();
+            let sub_guid = sub_info.guid.to_ascii_lowercase();
+            if main_guid != sub_guid {
+                self.guid_mappings.insert(rel_path.clone(), (main_guid, sub_guid));
+            }
+        }
+
+        if !self.guid_mappings.is_empty() {
+            let sub_metas = self.scan_meta_files(&self.subordinate_project)?;
+            self.collisions = self.find_collisions(&sub_metas);
+        }
+        Ok(())
+    }
+
+    /// Keep only the mappings for the given meta files (relative paths), e.g. after asking the
+    /// user which to apply. Collisions are recomputed, since skipped assets keep their GUIDs.
+    pub fn retain_mappings(&mut self, approved: &HashSet<PathBuf>) -> Result<()> {
+        self.guid_mappings.retain(|rel_path, _| approved.contains(rel_path));
+        let sub_metas = self.scan_meta_files(&self.subordinate_project)?;
+        self.collisions = self.find_collisions(&sub_metas);
+        Ok(())
+    }
+
+    /// Keep only the mappings whose subordinate or main GUID is one of `guids` (lowercase),
+    /// returning the GUIDs that matched a mapping
+    pub fn retain_guids(&mut self, guids: &HashSet<String>) -> Result<HashSet<String>> {
+        let mut matched = HashSet::new();
+        let mut approved = HashSet::new();
+        for (rel_path, (main_guid, sub_guid)) in &self.guid_mappings {
+            for guid in [sub_guid, main_guid] {
+                if guids.contains(guid) {
+                    matched.insert(guid.clone());
+                    approved.insert(rel_path.clone());
+                }
+            }
+        }
+        self.retain_mappings(&approved)?;
+        Ok(matched)
+    }
+
+    /// Continue the interrupted sync recorded in a checkpoint: its GUID changes replace the ones
+    /// just scanned, which lack the meta files that sync already rewrote. Fails if the scan
+    /// found changes the checkpoint does not know, since then it was written for another plan.
+    /// Returns the number of steps already done.
+    pub fn resume_from_checkpoint(&mut self, path: &Path) -> Result<usize> {
+        let checkpoint = Checkpoint::open(path)?;
+        let planned = checkpoint.mappings();
+        for (rel_path, mapping) in &self.guid_mappings {
+            if planned.get(rel_path) != Some(mapping) {
+                return Err(GuidSyncError::CheckpointMismatch {
+                    path: path.to_path_buf(),
+                    reason: format!(
+                        "was written for different GUID changes ({} is not among them); delete it to start over",
+                        rel_path.display()
+                    ),
+                });
+            }
+        }
+        for rel_path in planned.keys() {
+            if !self.guid_mappings.contains_key(rel_path) && !checkpoint.is_done(&CheckpointStep::Meta(rel_path.clone())) {
+                return Err(GuidSyncError::CheckpointMismatch {
+                    path: path.to_path_buf(),
+                    reason: format!("plans to change {}, which no longer differs; delete it to start over", rel_path.display()),
+                });
+            }
+        }
+        self.guid_mappings = planned;
+        Ok(checkpoint.completed())
+    }
+
+    /// Mappings whose old GUID no file in the subordinate project references, so syncing
+    /// them only rewrites the meta file. Sorted by path. `counts` comes from `reference_counts`.
+    pub fn unreferenced_mappings(&self, counts: &HashMap<String, usize>) -> Vec<PathBuf> {
+        let mut unreferenced: Vec<PathBuf> = self
+            .guid_mappings
+            .iter()
+            .filter(|(_, (_, sub_guid))| !counts.contains_key(sub_guid))
+            .map(|(rel_path, _)| rel_path.clone())
+            .collect();
+        unreferenced.sort();
+        unreferenced
+    }
+
+    /// Drop the mappings `unreferenced_mappings` returns, returning how many were dropped
+    pub fn skip_unreferenced(&mut self) -> Result<usize> {
+        let unreferenced: HashSet<PathBuf> =
+            self.unreferenced_mappings(&self.reference_counts()).into_iter().collect();
+        if unreferenced.is_empty() {
+            return Ok(0);
+        }
+        let approved = self
+            .guid_mappings
+            .keys()
+            .filter(|rel_path| !unreferenced.contains(*rel_path))
+            .cloned()
+            .collect();
+        self.retain_mappings(&approved)?;
+        Ok(unreferenced.len())
+    }
+
+    /// Number of references per lowercase GUID across the subordinate's reference files,
+    /// found in a single walk over them
+    pub fn reference_counts(&self) -> HashMap<String, usize> {
+        self.count_references_in(&self.unity_yaml_files(&self.subordinate_project))
+    }
+
+    /// Fill in how often the main project references each operation's new GUID, to compare
+    /// the risk of syncing in the other direction
+    pub fn add_main_reference_counts(&self, report: &mut SyncOperationsReport) {
+        let counts = self.count_references_in(&self.unity_yaml_files(&self.main_project));
+        for op in &mut report.operations {
+            op.main_reference_count = Some(counts.get(&op.new_guid).copied().unwrap_or(0));
+        }
+    }
+
+    /// Fill in the subordinate's references to GUIDs that no meta file in either project has.
+    /// Unity's built-in resources, whose GUIDs start with 16 zeros, have no meta file and are
+    /// left out. The include/exclude filter limits which files are searched, not which meta
+    /// files count as known.
+    pub fn add_dangling_references(&self, report: &mut SyncOperationsReport) {
+        let known = self.known_guids();
+        let per_file: Vec<(PathBuf, HashMap<String, usize>)> = self
+            .unity_yaml_files(&self.subordinate_project)
+            .into_par_iter()
+            .filter_map(|path| {
+                let mut counts = count_guid_references(&self.read_text(&path).ok()?);
+                counts.retain(|guid, _| !known.contains(guid) && !guid.starts_with(BUILTIN_GUID_PREFIX));
+                (!counts.is_empty()).then(|| (self.project_relative(&self.subordinate_project, &path), counts))
+            })
+            .collect();
+
+        let mut dangling: BTreeMap<String, Vec<(PathBuf, usize)>> = BTreeMap::new();
+        for (path, counts) in per_file {
+            for (guid, count) in counts {
+                dangling.entry(guid).or_default().push((path.clone(), count));
+            }
+        }
+        report.dangling_references = dangling
+            .into_iter()
+            .map(|(guid, mut files)| {
+                files.sort();
+                DanglingReference { guid, files }
+            })
+            .collect();
+    }
+
+    /// The lowercase GUIDs of every meta file in both projects, regardless of the filter
+    fn known_guids(&self) -> HashSet<String> {
+        let metas: Vec<PathBuf> = [&self.main_project, &self.subordinate_project]
+            .into_iter()
+            .flat_map(|project| self.walk_project(project))
+            .map(|entry| entry.into_path())
+            .filter(|path| is_meta_file(path) && !path.components().any(|c| c.as_os_str() == "Library"))
+            .collect();
+        metas
+            .into_par_iter()
+            .filter_map(|path| MetaFile::get_guid_from_file(&path).ok())
+            .map(|guid| guid.to_ascii_lowercase())
+            .collect()
+    }
+
+    /// Census of a single project: its meta files, GUIDs and most referenced GUIDs
+    pub fn project_stats(&self, project: &Path) -> Result<ProjectStats> {
+        let mut stats = ProjectStats::default();
+        let mut paths_by_guid: HashMap<String, PathBuf> = HashMap::new();
+        self.visit_meta_files(project, None, |rel_path, info| {
+            stats.meta_files += 1;
+            if info.is_folder {
+                stats.folder_assets += 1;
+            } else {
+                stats.file_assets += 1;
+            }
+            paths_by_guid.entry(info.guid).or_insert(rel_path);
+        })?;
+        stats.distinct_guids = paths_by_guid.len();
+
+        let yaml_files = self.unity_yaml_files(project);
+        stats.reference_files = yaml_files.len();
+        let mut counts: Vec<(String, usize)> = self.count_references_in(&yaml_files).into_iter().collect();
+        counts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        stats.top_referenced = counts
+            .into_iter()
+            .take(TOP_REFERENCED_GUIDS)
+            .map(|(guid, references)| ReferencedGuid {
+                meta_path: paths_by_guid.get(&guid).cloned(),
+                guid,
+                references,
+            })
+            .collect();
+        Ok(stats)
+    }
+
+    /// A project's reference files that pass the filter and `is_unity_yaml`, sorted by path
+    fn unity_yaml_files(&self, project: &Path) -> Vec<PathBuf> {
+        self.reference_files(project)
+            .into_par_iter()
+            .filter(|path| self.is_unity_yaml(path))
+            .collect()
+    }
+
+    pub fn get_collisions(&self) -> &[GuidCollision] {
+        &self.collisions
+    }
+
+    /// Error out if the scan found GUID collisions, unless they were explicitly allowed
+    pub fn check_collisions(&self, options: &SyncOptions) -> Result<()> {
+        if !self.collisions.is_empty() && !options.allow_collisions {
+            return Err(GuidSyncError::PathCollision { count: self.collisions.len() });
+        }
+        Ok(())
+    }
+
+    /// Find mappings whose target GUID would end up shared with a different asset in the
+    /// subordinate project after syncing (assets that are themselves remapped don't count)
+    fn find_collisions(&self, sub_metas: &HashMap<PathBuf, String>) -> Vec<GuidCollision> {
+        let mut final_guids: HashMap<&str, &PathBuf> = HashMap::new();
+        for (rel_path, sub_guid) in sub_metas {
+            if !self.guid_mappings.contains_key(rel_path) {
+                final_guids.insert(sub_guid.as_str(), rel_path);
+            }
+        }
+
+        let mut collisions = Vec::new();
+        for (rel_path, (main_guid, _sub_guid)) in self.sorted_mappings() {
+            if let Some(existing_path) = final_guids.get(main_guid.as_str()) {
+                collisions.push(GuidCollision {
+                    guid: main_guid.clone(),
+                    asset_path: rel_path.clone(),
+                    existing_path: (*existing_path).clone(),
+                });
+            }
+        }
+        collisions.sort_by(|a, b| a.asset_path.cmp(&b.asset_path));
+        collisions
+    }
+
+    /// Subordinate GUIDs shared by meta files that map to different main GUIDs. Their meta
+    /// files can each be synced, but the references to the shared GUID are ambiguous.
+    pub fn ambiguous_guids(&self) -> Vec<AmbiguousGuid> {
+        let mut candidates: BTreeMap<&str, Vec<(PathBuf, String)>> = BTreeMap::new();
+        for (rel_path, (main_guid, sub_guid)) in self.sorted_mappings() {
+            candidates.entry(sub_guid).or_default().push((rel_path.clone(), main_guid.clone()));
+        }
+        candidates
+            .into_iter()
+            .filter(|(_, candidates)| candidates.iter().any(|(_, main_guid)| *main_guid != candidates[0].1))
+            .map(|(sub_guid, candidates)| AmbiguousGuid { sub_guid: sub_guid.to_string(), candidates })
+            .collect()
+    }
+
+    /// Error out if there are ambiguous GUIDs and no way to resolve them was chosen
+    pub fn check_ambiguous(&self, options: &SyncOptions) -> Result<()> {
+        let ambiguous = self.ambiguous_guids();
+        if !ambiguous.is_empty() && options.resolve_ambiguous.is_none() {
+            return Err(GuidSyncError::AmbiguousGuids { count: ambiguous.len() });
+        }
+        Ok(())
+    }
+
+    /// The ambiguous subordinate GUIDs a sync leaves alone
+    fn skipped_ambiguous(&self, options: &SyncOptions) -> HashSet<String> {
+        if options.resolve_ambiguous != Some(AmbiguousResolution::Skip) {
+            return HashSet::new();
+        }
+        self.ambiguous_guids().into_iter().map(|ambiguous| ambiguous.sub_guid).collect()
+    }
+
+    /// Find GUIDs shared by several meta files in one project, a known form of Unity corruption
+    pub fn find_duplicate_guids(&self, project: &Path) -> Result<Vec<DuplicateGuid>> {
+        let mut paths_by_guid: HashMap<String, Vec<PathBuf>> = HashMap::new();
+        for (rel_path, guid) in self.scan_meta_files(project)? {
+            paths_by_guid.entry(guid).or_default().push(rel_path);
+        }
+
+        let mut duplicates: Vec<DuplicateGuid> = paths_by_guid
+            .into_iter()
+            .filter(|(_, paths)| paths.len() > 1)
+            .map(|(guid, mut meta_paths)| {
+                meta_paths.sort();
+                DuplicateGuid { guid, meta_paths }
+            })
+            .collect();
+        duplicates.sort_by(|a, b| a.meta_paths[0].cmp(&b.meta_paths[0]));
+        Ok(duplicates)
+    }
+
+    /// Read every meta file of a project (honoring the scan options), returning
+    /// relative meta path -> lowercase GUID. Unreadable meta files are skipped with a warning.
+    pub fn scan_meta_files(&self, project_path: &Path) -> Result<HashMap<PathBuf, String>> {
+        self.scan_meta_files_with_cache(project_path, None)
+    }
+
+    /// `scan_meta_files`, taking unchanged meta files from the cache and refreshing it
+    fn scan_meta_files_with_cache(
+        &self,
+        project_path: &Path,
+        cache: Option<&mut ScanCache>,
+    ) -> Result<HashMap<PathBuf, String>> {
+        let mut mappings = HashMap::new();
+        self.visit_meta_files(project_path, cache, |relative_path, info| {
+            mappings.insert(relative_path, info.guid);
+        })?;
+        Ok(mappings)
+    }
+
+    /// Read every meta file of a project one at a time, passing its relative path and
+    /// parsed contents (with the GUID in lowercase) to `visit` without collecting them
+    fn visit_meta_files(
+        &self,
+        project_path: &Path,
+        mut cache: Option<&mut ScanCache>,
+        mut visit: impl FnMut(PathBuf, MetaInfo),
+    ) -> Result<()> {
+        let mut seen = HashSet::new();
+        let mut scanned = 0;
+        self.observer.on_scan_started(project_path);
+
+        for entry in self.walk_project(project_path) {
+            let path = entry.path();
+            if path.extension().and_then(|s| s.to_str()) == Some("meta") {
+                // Skip Unity's generated Library cache, it is not part of the project source
+                if path.components().any(|c| c.as_os_str() == "Library") {
+                    continue;
+                }
+                
+                let relative_path = &self.project_relative(project_path, path);
+                if !self.scan_options.filter.is_meta_match(relative_path) {
+                    continue;
+                }
+                
+                scanned += 1;
+                self.observer.on_file_scanned(relative_path);
+                let modified = match cache {
+                    Some(_) => entry.metadata().ok().and_then(|metadata| metadata.modified().ok()),
+                    None => None,
+                };
+                let cached = match (cache.as_deref(), modified) {
+                    (Some(cache), Some(modified)) => cache.get(relative_path, modified).map(|cached| MetaInfo {
+                        guid: cached.guid.clone(),
+                        is_folder: cached.is_folder,
+                        // Not cached, nothing reads it during a scan
+                        importer: None,
+                    }),
+                    _ => None,
+                };
+                let parsed = match cached {
+                    Some(info) => Ok(info),
+                    None => MetaFile::parse(path),
+                };
+                if let (Some(cache), Some(modified), Ok(info)) = (cache.as_deref_mut(), modified, &parsed) {
+                    seen.insert(relative_path.clone());
+                    cache.entries.insert(
+                        relative_path.clone(),
+                        CachedMeta { guid: info.guid.clone(), is_folder: info.is_folder, modified },
+                    );
+                }
+                
+                match parsed {
+                    Ok(info) if info.is_folder && self.scan_options.skip_folders => {}
+                    Ok(info) => {
+                        // Normalize case so ABCD... and abcd... compare equal
+                        let guid = info.guid.to_ascii_lowercase();
+                        visit(relative_path.to_path_buf(), MetaInfo { guid, ..info });
+                    }
+                    Err(e) => {
+                        // Log error but continue scanning
+                        warn!("Could not read {}: {}", path.display(), e);
+                        self.record_skipped(path, &e);
+                    }
+                }
+            }
+        }
+
+        self.observer.on_scan_finished(project_path, scanned);
+        debug!("Scanned {} meta files in {}", scanned, project_path.display());
+        // Forget meta files that were deleted since the cache was written
+        if let Some(cache) = cache {
+            cache.entries.retain(|rel_path, _| seen.contains(rel_path));
+        }
+        Ok(())
+    }
+
+    /// Remember a meta file the scan could not read, once per path
+    fn record_skipped(&self, path: &Path, reason: &GuidSyncError) {
+        let mut skipped = self.skipped_files.lock().unwrap_or_else(|e| e.into_inner());
+        if !skipped.iter().any(|(skipped_path, _)| skipped_path == path) {
+            skipped.push((path.to_path_buf(), error_chain(reason)));
+        }
+    }
+
+    /// With `fail_on_warning`, turn skipped files into an error listing all of them
+    fn check_skipped(&self, skipped: Vec<(PathBuf, String)>) -> Result<()> {
+        if !self.scan_options.fail_on_warning || skipped.is_empty() {
+            return Ok(());
+        }
+        Err(GuidSyncError::UnreadableFiles(skipped))
+    }
+
+    /// Reference files that would be skipped by the rewrite because they are not valid UTF-8
+    fn unreadable_reference_files(&self) -> Vec<(PathBuf, String)> {
+        self.reference_candidates()
+            .par_iter()
+            .filter(|path| self.is_unity_yaml(path))
+            .filter_map(|path| match self.read_text(path) {
+                Ok(_) => None,
+                Err(e) => Some((path.clone(), e.to_string())),
+            })
+            .collect()
+    }
+
+    /// Meta files the scans so far could not read or parse (path, reason)
+    pub fn skipped_files(&self) -> Vec<(PathBuf, String)> {
+        self.skipped_files.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    pub fn sync_guids(&self, options: &SyncOptions) -> Result<SyncReport> {
+        if self.guid_mappings.is_empty() && self.reference_only_mappings.is_empty() {
+            self.observer.on_nothing_to_sync();
+            return Ok(SyncReport::new());
+        }
+
+        info!(
+            "Syncing GUIDs in {} ({})",
+            self.subordinate_project.display(),
+            if options.dry_run { "dry run" } else { "live" }
+        );
+
+        self.check_collisions(options)?;
+        self.check_ambiguous(options)?;
+        // Check up front, so a strict sync fails before anything has been written
+        let mut skipped = self.skipped_files();
+        if self.scan_options.fail_on_warning {
+            skipped.extend(self.unreadable_reference_files());
+        }
+        self.check_skipped(skipped)?;
+        self.warn_serialization_mode()?;
+
+        let checkpoint = match options.checkpoint.as_deref().filter(|_| !options.dry_run) {
+            Some(path) if path.exists() => {
+                let checkpoint = Checkpoint::open(path)?;
+                if !checkpoint.matches(&self.guid_mappings) {
+                    return Err(GuidSyncError::CheckpointMismatch {
+                        path: path.to_path_buf(),
+                        reason: "was written for different GUID changes".to_string(),
+                    });
+                }
+                Some(checkpoint)
+            }
+            Some(path) => Some(Checkpoint::create(path, &self.guid_mappings)?),
+            None => None,
+        };
+        // A resumed sync fills the backup directory its first run started
+        let resuming = checkpoint.as_ref().is_some_and(|checkpoint| checkpoint.completed() > 0);
+
+        if let Some(backup_dir) = &options.backup_dir {
+            if !options.dry_run && backup_dir.exists() && !options.force && !resuming {
+                return Err(GuidSyncError::BackupExists(backup_dir.clone()));
+            }
+        }
+
+        let mut report = SyncReport::new();
+        report.skipped_files = self.skipped_files();
+
+        // Update meta files
+        let skipped_ambiguous = self.skipped_ambiguous(options);
+        let meta_mappings = if options.refs_only || options.only_file.is_some() {
+            Vec::new()
+        } else {
+            self.sorted_mappings()
+        };
+        let meta_mappings = meta_mappings.into_iter().filter(|(rel_path, (_, sub_guid))| {
+            let skip = skipped_ambiguous.contains(sub_guid);
+            if skip {
+                debug!("Skipping {}: its GUID {} is ambiguous", rel_path.display(), sub_guid);
+            }
+            !skip
+        });
+        for (rel_path, (main_guid, sub_guid)) in meta_mappings {
+            let meta_path = self.subordinate_project.join(rel_path);
+            let step = CheckpointStep::Meta(rel_path.clone());
+            if checkpoint.as_ref().is_some_and(|checkpoint| checkpoint.is_done(&step)) {
+                debug!("Skipping {}: updated before the sync was interrupted", meta_path.display());
+            } else if self.update_meta_file(&meta_path, main_guid, options, &mut report)? {
+                if let Some(checkpoint) = &checkpoint {
+                    checkpoint.record(&step)?;
+                }
+            } else {
+                // Left unchanged, so references to its GUID are left alone too
+                continue;
+            }
+            let change = GuidChange {
+                meta_path: rel_path.clone(),
+                old_guid: sub_guid.clone(),
+                new_guid: main_guid.clone(),
+                direction: self.direction,
+            };
+            self.observer.on_meta_updated(&change);
+            report.meta_files_changed += 1;
+            report.guid_changes.push(change);
+        }
+
+        // Update references in all Unity files
+        if options.meta_only {
+            debug!("Skipping the reference update: --meta-only");
+        } else {
+            self.update_guid_references_with_report(options, checkpoint.as_ref(), &mut report)?;
+        }
+        if report.binary_files > 0 {
+            warn!(
+                "{} binary reference files were skipped, GUID references inside them were not updated",
+                report.binary_files
+            );
+        }
+
+        self.observer.on_sync_finished(&report, options);
+        if let Some(checkpoint) = checkpoint {
+            debug!("Removing checkpoint {}", checkpoint.path().display());
+            checkpoint.remove()?;
         }
+        Ok(report)
     }
-    
-    pub fn get_difference_count(&self) -> usize {
-        self.guid_mappings.len()
+
+    /// Warn when the subordinate project does not force text serialization, since its binary
+    /// scenes and prefabs are skipped and the sync cannot be complete
+    fn warn_serialization_mode(&self) -> Result<()> {
+        match SerializationMode::read(&self.subordinate_project)? {
+            Some(mode) if mode != SerializationMode::ForceText => warn!(
+                "{} uses {} asset serialization: binary scenes, prefabs and assets cannot be rewritten and will be skipped. \
+                 Switch to Force Text (Project Settings > Editor > Asset Serialization) before syncing.",
+                self.subordinate_project.parent().unwrap_or(&self.subordinate_project).display(),
+                mode
+            ),
+            _ => {}
+        }
+        Ok(())
     }
 
-    pub fn scan_projects(&mut self) -> Result<()> {
-        println!("{}", "Scanning projects for GUID mappings...".bright_blue());
-        
-        let main_metas = self.scan_meta_files(&self.main_project)?;
-        let sub_metas = self.scan_meta_files(&self.subordinate_project)?;
+    /// Rewrite a meta file's own GUID. False if the file could not be written, which is
+    /// recorded in the report's failed files instead of stopping the sync.
+    fn update_meta_file(&self, path: &Path, new_guid: &str, options: &SyncOptions, report: &mut SyncReport) -> Result<bool> {
+        let content = fs::read_to_string(path)
+            .io_context(|| format!("Failed to read meta file: {}", path.display()))?;
+        let relative_path = self.project_relative(&self.subordinate_project, path);
+        if options.dry_run {
+            info!("Would update {}", path.display());
+        } else {
+            self.backup_file(path, options, report)?;
+            if let Err(e) = MetaFile::update_guid_in_file(path, new_guid) {
+                warn!("Could not update meta file {}: {:#}", path.display(), e);
+                report.failed_files.push((path.to_path_buf(), error_chain(&e)));
+                return Ok(false);
+            }
+            info!("Updated {}", path.display());
+        }
 
-        for (rel_path, main_guid) in &main_metas {
-            if let Some(sub_guid) = sub_metas.get(rel_path) {
-                if main_guid != sub_guid {
-                    println!(
-                        "{}: {} -> {}",
-                        format!("GUID difference found for {}", rel_path.display()).yellow(),
-                        sub_guid.red(),
-                        main_guid.green()
-                    );
-                    self.guid_mappings.insert(
-                        rel_path.clone(),
-                        (main_guid.clone(), sub_guid.clone()),
-                    );
-                }
+        if let Some(new_content) = MetaFile::rewrite_guid(&content, new_guid) {
+            report.record_size(&relative_path, content.len(), new_content.len());
+            if options.dry_run && options.show_diff {
+                let diff = unified_diff(&relative_path, &content, &new_content);
+                report.diffs.push((relative_path, diff));
             }
+            self.write_shadow(path, new_content.as_bytes(), options)?;
         }
+        Ok(true)
+    }
 
-        println!(
-            "{}",
-            format!("Found {} GUID differences", self.guid_mappings.len()).bright_yellow()
-        );
-        Ok(())
+    fn is_reference_file(&self, path: &Path) -> bool {
+        self.scan_options.is_reference_file(path)
     }
 
-    fn scan_meta_files(&self, project_path: &Path) -> Result<HashMap<PathBuf, String>> {
-        let mut mappings = HashMap::new();
+    /// Whether references in this file are counted and rewritten: the single check shared by
+    /// `sync`, `report` and `verify`. It needs a reference extension and a YAML header, except
+    /// for assembly definitions, which are JSON.
+    fn is_unity_yaml(&self, path: &Path) -> bool {
+        self.is_reference_file(path) && (is_json_asset(path) || has_yaml_header(path, self.scan_options.try_encodings))
+    }
 
-        for entry in WalkDir::new(project_path)
-            .into_iter()
-            .filter_map(|e| e.ok())
-        {
-            let path = entry.path();
-            if path.extension().and_then(|s| s.to_str()) == Some("meta") {
-                // Skip Library folder to avoid UTF-8 BOM issues
-                if path.components().any(|c| c.as_os_str() == "Library") {
-                    continue;
-                }
-                
-                match MetaFile::get_guid_from_file(path) {
-                    Ok(guid) => {
-                        let relative_path = path
-                            .strip_prefix(project_path)?
-                            .to_path_buf();
-                        mappings.insert(relative_path, guid);
-                    }
-                    Err(e) => {
-                        // Log error but continue scanning
-                        eprintln!("Warning: Could not read {}: {}", path.display(), e);
-                    }
-                }
-            }
-        }
+    /// All reference files in the subordinate project that pass the filter, sorted by path
+    fn reference_candidates(&self) -> Vec<PathBuf> {
+        self.reference_files(&self.subordinate_project)
+    }
 
-        Ok(mappings)
+    /// All reference files in a project that pass the filter, sorted by path
+    fn reference_files(&self, project: &Path) -> Vec<PathBuf> {
+        let mut candidates: Vec<PathBuf> = self
+            .walk_project(project)
+            .map(|e| e.into_path())
+            .chain(self.project_settings_files(project))
+            // Skip meta files and non-files
+            .filter(|path| path.is_file() && self.is_reference_file(path))
+            .filter(|path| self.scan_options.filter.is_match(&self.project_relative(project, path)))
+            .collect();
+        candidates.sort();
+        candidates
     }
 
-    pub fn sync_guids(&self, dry_run: bool, verbose: bool) -> Result<SyncReport> {
-        if self.guid_mappings.is_empty() {
-            println!("{}", "No GUID differences to resolve!".green());
-            return Ok(SyncReport::new());
-        }
+    /// The mappings in relative path order, so output and writes are the same on every run
+    fn sorted_mappings(&self) -> Vec<(&PathBuf, &(String, String))> {
+        let mut mappings: Vec<_> = self.guid_mappings.iter().collect();
+        mappings.sort_by(|a, b| a.0.cmp(b.0));
+        mappings
+    }
 
-        if verbose {
-            println!(
-                "{}",
-                format!(
-                    "Syncing GUIDs in subordinate project ({})...",
-                    if dry_run { "DRY RUN" } else { "LIVE" }
-                )
-                .bright_blue()
-            );
+    /// All meta files in the subordinate project that pass the filter, for `rewrite_meta_refs`
+    fn meta_reference_candidates(&self) -> Vec<PathBuf> {
+        self.walk_project(&self.subordinate_project)
+            .map(|e| e.into_path())
+            .filter(|path| path.is_file() && is_meta_file(path))
+            // Skip Unity's generated Library cache, like the meta file scan does
+            .filter(|path| !path.components().any(|c| c.as_os_str() == "Library"))
+            .filter(|path| {
+                let relative_path = self.project_relative(&self.subordinate_project, path);
+                self.scan_options.filter.is_meta_match(&relative_path)
+            })
+            .collect()
+    }
+
+    /// Build reverse mapping: sub_guid -> main_guid
+    fn reverse_guid_map(&self) -> HashMap<&str, &str> {
+        let mut map = HashMap::new();
+        // In path order, so a GUID shared by several meta files maps like the first of them
+        for (_, (main, sub)) in self.sorted_mappings() {
+            map.entry(sub.as_str()).or_insert(main.as_str());
         }
+        for (sub, main) in &self.reference_only_mappings {
+            map.entry(sub.as_str()).or_insert(main.as_str());
+        }
+        map
+    }
 
-        let mut report = SyncReport::new();
+    /// Whether a subordinate file passes the include/exclude filter
+    fn is_selected(&self, path: &Path) -> bool {
+        let relative_path = self.project_relative(&self.subordinate_project, path);
+        self.scan_options.filter.is_match(&relative_path)
+    }
 
-        // Update meta files
-        for (rel_path, (main_guid, _sub_guid)) in &self.guid_mappings {
-            let meta_path = self.subordinate_project.join(rel_path);
-            self.update_meta_file(&meta_path, main_guid, dry_run, verbose)?;
-            report.meta_files_changed += 1;
+    /// Walk every file and folder of a project: its Assets folder, plus the embedded
+    /// packages next to it when `include_packages` is set
+    fn walk_project(&self, project: &Path) -> impl Iterator<Item = walkdir::DirEntry> {
+        let follow_symlinks = self.scan_options.follow_symlinks;
+        let roots: Vec<PathBuf> = std::iter::once(project.to_path_buf()).chain(self.packages_root(project)).collect();
+        let real_roots: Vec<(PathBuf, PathBuf)> = roots.iter().map(|root| (root.clone(), canonical(root))).collect();
+        let mut seen = HashSet::new();
+        roots
+            .into_iter()
+            // Symlink loops come out as errors and are skipped like any unreadable entry
+            .flat_map(move |root| {
+                WalkDir::new(root)
+                    .follow_links(follow_symlinks)
+                    .sort_by_file_name()
+                    .into_iter()
+                    .filter_map(|e| e.ok())
+            })
+            // Through symlinks one real file can show up under several paths. A file inside a
+            // walked root is kept at its own path, one outside of them at the first path found.
+            .filter(move |entry| {
+                if !follow_symlinks || entry.file_type().is_dir() {
+                    return true;
+                }
+                let real = canonical(entry.path());
+                let direct = real_roots.iter().any(|(root, real_root)| {
+                    entry.path().strip_prefix(root).is_ok_and(|relative| real_root.join(relative) == real)
+                });
+                let inside = real_roots.iter().any(|(_, real_root)| real.starts_with(real_root));
+                (direct || !inside) && seen.insert(real)
+            })
+    }
+
+    /// The `Packages` folder next to a project's Assets folder, if packages are scanned at all.
+    /// Cached registry packages live under `Library/PackageCache` and are never included.
+    fn packages_root(&self, project: &Path) -> Option<PathBuf> {
+        if !self.scan_options.include_packages {
+            return None;
         }
+        let packages = project.parent()?.join("Packages");
+        packages.is_dir().then_some(packages)
+    }
 
-        // Update references in all Unity files
-        self.update_guid_references_with_report(dry_run, verbose, &mut report)?;
+    /// The `ProjectSettings/*.asset` files next to a project's Assets folder, sorted by path,
+    /// if `include_project_settings` is set
+    fn project_settings_files(&self, project: &Path) -> Vec<PathBuf> {
+        let Some(settings) = self.project_settings_root(project) else {
+            return Vec::new();
+        };
+        let mut files: Vec<PathBuf> = match fs::read_dir(&settings) {
+            Ok(entries) => entries
+                .filter_map(|entry| entry.ok())
+                .map(|entry| entry.path())
+                .filter(|path| path.is_file() && path.extension().is_some_and(|ext| ext == "asset"))
+                .collect(),
+            Err(e) => {
+                warn!("Could not read {}: {}", settings.display(), e);
+                Vec::new()
+            }
+        };
+        files.sort();
+        files
+    }
 
-        if dry_run {
-            report.print();
+    fn project_settings_root(&self, project: &Path) -> Option<PathBuf> {
+        if !self.scan_options.include_project_settings {
+            return None;
         }
+        let settings = project.parent()?.join("ProjectSettings");
+        settings.is_dir().then_some(settings)
+    }
 
-        println!("{}", "GUID sync completed!".bright_green());
-        Ok(report)
+    /// Path relative to a project's Assets folder. Package files come out as `../Packages/...`,
+    /// which keeps them apart from an `Assets/Packages` folder and joins back onto Assets as is,
+    /// and project settings as `../ProjectSettings/...`.
+    fn project_relative(&self, project: &Path, path: &Path) -> PathBuf {
+        if let Ok(relative_path) = path.strip_prefix(project) {
+            return relative_path.to_path_buf();
+        }
+        if let Some(packages) = self.packages_root(project) {
+            if let Ok(relative_path) = path.strip_prefix(&packages) {
+                return Path::new("..").join("Packages").join(relative_path);
+            }
+        }
+        if let Some(settings) = self.project_settings_root(project) {
+            if let Ok(relative_path) = path.strip_prefix(&settings) {
+                return Path::new("..").join("ProjectSettings").join(relative_path);
+            }
+        }
+        path.to_path_buf()
     }
 
-    fn update_meta_file(&self, path: &Path, new_guid: &str, dry_run: bool, verbose: bool) -> Result<()> {
-        if dry_run && verbose {
-            println!("  {} {}", "[DRY RUN]".cyan(), path.display());
+    /// Copy a file into the backup directory (if one is configured) before it gets modified,
+    /// preserving its path relative to the subordinate project
+    fn backup_file(&self, path: &Path, options: &SyncOptions, report: &mut SyncReport) -> Result<()> {
+        let Some(backup_dir) = &options.backup_dir else {
             return Ok(());
-        }
+        };
 
-        if !dry_run {
-            MetaFile::update_guid_in_file(path, new_guid)
-                .with_context(|| format!("Failed to update meta file: {}", path.display()))?;
-            if verbose {
-                println!("  {} {}", "Updated".green(), path.display());
-            }
+        let backup_path = self.mirrored_path(backup_dir, path);
+        if let Some(parent) = backup_path.parent() {
+            fs::create_dir_all(parent)
+                .io_context(|| format!("Failed to create backup directory: {}", parent.display()))?;
         }
+        fs::copy(path, &backup_path).io_context(|| {
+            format!("Failed to back up {} to {}, aborting sync", path.display(), backup_path.display())
+        })?;
+
+        report.backed_up_files.insert(path.to_path_buf(), backup_path);
         Ok(())
     }
 
-    fn update_guid_references_with_report(&self, dry_run: bool, verbose: bool, report: &mut SyncReport) -> Result<()> {
-        if verbose {
-            println!("{}", "Updating GUID references in Unity files...".bright_blue());
+    /// Where a subordinate file goes in a backup or shadow directory: its path relative to the
+    /// project, with package files under `Packages~` and project settings under `ProjectSettings~`,
+    /// which cannot clash with folders inside Assets
+    fn mirrored_path(&self, dir: &Path, path: &Path) -> PathBuf {
+        let relative_path = self.project_relative(&self.subordinate_project, path);
+        let relative_path = if let Ok(package_path) = relative_path.strip_prefix(Path::new("..").join("Packages")) {
+            Path::new("Packages~").join(package_path)
+        } else if let Ok(settings_path) = relative_path.strip_prefix(Path::new("..").join("ProjectSettings")) {
+            Path::new("ProjectSettings~").join(settings_path)
+        } else {
+            relative_path
+        };
+        dir.join(relative_path)
+    }
+
+    /// On a dry run with a shadow directory, write what a file would contain after the sync
+    fn write_shadow(&self, path: &Path, contents: &[u8], options: &SyncOptions) -> Result<()> {
+        let Some(shadow_dir) = options.shadow_dir.as_ref().filter(|_| options.dry_run) else {
+            return Ok(());
+        };
+        let shadow_path = self.mirrored_path(shadow_dir, path);
+        if let Some(parent) = shadow_path.parent() {
+            fs::create_dir_all(parent)
+                .io_context(|| format!("Failed to create shadow directory: {}", parent.display()))?;
         }
+        fs::write(&shadow_path, contents)
+            .io_context(|| format!("Failed to write shadow file: {}", shadow_path.display()))?;
+        debug!("Wrote shadow copy of {} to {}", path.display(), shadow_path.display());
+        Ok(())
+    }
 
-        let guid_regex = Regex::new(r"guid:\s*([a-f0-9]{32})")?;
-        let file_id_regex = Regex::new(r"\{fileID:\s*\d+,\s*guid:\s*([a-f0-9]{32}),\s*type:\s*\d+\}")?;
+    fn update_guid_references_with_report(
+        &self,
+        options: &SyncOptions,
+        checkpoint: Option<&Checkpoint>,
+        report: &mut SyncReport,
+    ) -> Result<()> {
+        info!("Updating GUID references in Unity files");
 
-        for entry in WalkDir::new(&self.subordinate_project)
-            .into_iter()
-            .filter_map(|e| e.ok())
-        {
-            let path = entry.path();
-            
-            // Skip meta files and non-files
-            if !path.is_file() || path.extension() == Some(std::ffi::OsStr::new("meta")) {
-                continue;
-            }
-            
-            // Check if file is likely a Unity YAML file by checking first line
-            if let Ok(file) = std::fs::File::open(path) {
-                let reader = BufReader::new(file);
-                if let Some(Ok(first_line)) = reader.lines().next() {
-                    // Unity YAML files typically start with %YAML
-                    if first_line.starts_with("%YAML") || first_line.starts_with("---") {
-                        self.update_file_guids_with_report(path, &guid_regex, &file_id_regex, dry_run, verbose, report)?;
+        // Collect candidates up front so they can be processed in parallel
+        let mut candidates = match &options.only_file {
+            Some(file) => vec![file.clone()],
+            None => self.reference_candidates(),
+        };
+        if options.rewrite_meta_refs && options.only_file.is_none() {
+            candidates.extend(self.meta_reference_candidates());
+            candidates.sort();
+        }
+        let step = |path: &Path| CheckpointStep::References(self.project_relative(&self.subordinate_project, path));
+        if let Some(checkpoint) = checkpoint {
+            candidates.retain(|path| !checkpoint.is_done(&step(path)));
+        }
+        let mut guid_map = self.reverse_guid_map();
+        // Meta files that could not be written keep their old GUID, and their references with it
+        let unchanged: HashSet<&str> = report
+            .failed_files
+            .iter()
+            .filter_map(|(path, _)| self.guid_mappings.get(&self.project_relative(&self.subordinate_project, path)))
+            .map(|(_, sub_guid)| sub_guid.as_str())
+            .collect();
+        let skipped_ambiguous = self.skipped_ambiguous(options);
+        guid_map.retain(|sub_guid, _| !unchanged.contains(sub_guid) && !skipped_ambiguous.contains(*sub_guid));
+        let prefilter = GuidPrefilter::new(guid_map.keys().copied());
+
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(options.jobs.unwrap_or(0))
+            .build()?;
+        self.observer.on_references_started(ReferencePass::Rewrite { files: candidates.len() });
+        let fragments = pool.install(|| {
+            candidates
+                .par_iter()
+                .map(|path| {
+                    let fragment = self.update_file_guids_with_report(path, &guid_map, &prefilter, options)?;
+                    // A file that failed to write is retried when the sync is resumed
+                    if let Some(checkpoint) = checkpoint.filter(|_| fragment.failed_files.is_empty()) {
+                        checkpoint.record(&step(path))?;
                     }
+                    self.observer.on_reference_file_read();
+                    Ok(fragment)
+                })
+                .collect::<Result<Vec<SyncReport>>>()
+        });
+        self.observer.on_references_finished();
+        let fragments = fragments?;
+
+        // Merge in path order so the log is deterministic
+        for (path, fragment) in candidates.iter().zip(fragments) {
+            let file_ref_count = fragment.total_references_replaced();
+            if file_ref_count > 0 {
+                if options.dry_run {
+                    info!("Would update references in {} ({} references)", path.display(), file_ref_count);
+                } else {
+                    info!("Updated references in {} ({} references)", path.display(), file_ref_count);
                 }
+                self.observer.on_reference_replaced(&self.project_relative(&self.subordinate_project, path), file_ref_count);
             }
+            report.merge(fragment);
         }
 
         Ok(())
     }
 
+    /// Number of references per lowercase GUID across several files. Unreadable files are
+    /// left out, the scans report them.
+    fn count_references_in(&self, files: &[PathBuf]) -> HashMap<String, usize> {
+        files
+            .par_iter()
+            .filter_map(|path| self.read_text(path).ok())
+            .map(|content| count_guid_references(&content))
+            .reduce(HashMap::new, |mut counts, file_counts| {
+                for (guid, count) in file_counts {
+                    *counts.entry(guid).or_insert(0) += count;
+                }
+                counts
+            })
+    }
+
+    /// `read_reference_file` without the encoding, for the scans that only read
+    fn read_text(&self, path: &Path) -> std::io::Result<String> {
+        self.read_reference_file(path).map(|(content, _)| content)
+    }
+
+    /// Read a reference file as UTF-8. With `try_encodings`, a file that isn't UTF-8, or has the
+    /// zero bytes of UTF-16 text, is decoded as UTF-16 or Latin-1 and its encoding returned too.
+    fn read_reference_file(&self, path: &Path) -> std::io::Result<(String, Option<TextEncoding>)> {
+        if !self.scan_options.try_encodings {
+            return fs::read_to_string(path).map(|content| (content, None));
+        }
+        let bytes = match String::from_utf8(fs::read(path)?) {
+            Ok(content) if !content.contains('\0') => return Ok((content, None)),
+            Ok(content) => content.into_bytes(),
+            Err(e) => e.into_bytes(),
+        };
+        let encoding = TextEncoding::detect(&bytes);
+        match encoding.decode(&bytes) {
+            Some(content) => Ok((content, Some(encoding))),
+            None => Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!("not valid {}", encoding.name()),
+            )),
+        }
+    }
+
+    /// Rewrite GUID references in a single file, returning the report fragment for that file
     fn update_file_guids_with_report(
         &self,
         path: &Path,
-        guid_regex: &Regex,
-        file_id_regex: &Regex,
-        dry_run: bool,
-        verbose: bool,
-        report: &mut SyncReport,
-    ) -> Result<()> {
-        // Try to read file as UTF-8, skip if it fails
-        let content = match fs::read_to_string(path) {
-            Ok(content) => content,
+        guid_map: &HashMap<&str, &str>,
+        prefilter: &GuidPrefilter,
+        options: &SyncOptions,
+    ) -> Result<SyncReport> {
+        let mut report = SyncReport::new();
+
+        let is_meta = is_meta_file(path);
+        if !is_meta && !self.is_unity_yaml(path) {
+            if looks_binary(path) {
+                debug!("Skipping {}: binary file", path.display());
+                report.binary_files += 1;
+            } else {
+                debug!("Skipping {}: not a Unity YAML file", path.display());
+            }
+            return Ok(report);
+        }
+
+        // Try to read file as UTF-8 (or another encoding if allowed), skip if it fails
+        let (content, encoding) = match self.read_reference_file(path) {
+            Ok(read) => read,
             Err(e) => {
-                eprintln!("Warning: Could not read {} as UTF-8: {}", path.display(), e);
-                return Ok(());
+                warn!("Could not read {} as UTF-8: {}", path.display(), e);
+                report.skipped_files.push((path.to_path_buf(), e.to_string()));
+                return Ok(report);
             }
         };
-        let mut modified = false;
-        let mut new_content = content.clone();
+        if let Some(encoding) = encoding {
+            debug!("Reading {} as {}", path.display(), encoding.name());
+        }
+        if !prefilter.may_reference(&content) {
+            return Ok(report);
+        }
         let mut file_ref_count = 0;
 
-        // Build reverse mapping: sub_guid -> main_guid
-        let guid_map: HashMap<&str, &str> = self
-            .guid_mappings
-            .values()
-            .map(|(main, sub)| (sub.as_str(), main.as_str()))
-            .collect();
-
-        // Replace in guid: patterns
-        for cap in guid_regex.captures_iter(&content) {
-            if let Some(old_guid) = cap.get(1) {
-                if let Some(new_guid) = guid_map.get(old_guid.as_str()) {
-                    let old_match = cap.get(0).unwrap().as_str();
-                    let new_match = format!("guid: {}", new_guid);
-                    new_content = new_content.replace(old_match, &new_match);
-                    modified = true;
-                    file_ref_count += 1;
-                    *report.guid_reference_counts.entry(old_guid.as_str().to_string()).or_insert(0) += 1;
-                }
+        // Rewrite every guid: reference (including those inside {fileID: ..., guid: ..., type: ...})
+        // in a single pass over the original content, so each match is replaced exactly once.
+        // Everything outside the matches, including CRLF line endings, is left untouched.
+        // A UTF-8 BOM comes before the first line, so a top-level key there starts after it
+        let first_line_start = if content.starts_with('\u{feff}') { '\u{feff}'.len_utf8() } else { 0 };
+        let new_content = GUID_REGEX.replace_all(&content, |caps: &Captures| {
+            // A meta file's own GUID is the top-level key, only the embedded references are rewritten
+            let start = caps.get(0).map_or(0, |m| m.start());
+            if is_meta && (start == first_line_start || content.as_bytes()[start - 1] == b'\n') {
+                return caps[0].to_string();
             }
-        }
-
-        // Replace in {fileID: ..., guid: ..., type: ...} patterns
-        for cap in file_id_regex.captures_iter(&content) {
-            if let Some(old_guid) = cap.get(1) {
-                if let Some(new_guid) = guid_map.get(old_guid.as_str()) {
-                    let old_match = cap.get(0).unwrap().as_str();
-                    let new_match = old_match.replace(old_guid.as_str(), new_guid);
-                    new_content = new_content.replace(old_match, &new_match);
-                    modified = true;
+            let old_guid = caps["guid"].to_ascii_lowercase();
+            match guid_map.get(old_guid.as_str()) {
+                Some(new_guid) => {
                     file_ref_count += 1;
-                    *report.guid_reference_counts.entry(old_guid.as_str().to_string()).or_insert(0) += 1;
+                    // Keep the original spacing and any quotes around the GUID, like the meta file
+                    // rewrite does; a `type:` only changes when the mapping file overrides it
+                    let type_field = match (caps.name("type_key"), caps.name("type")) {
+                        (Some(key), Some(old_type)) => {
+                            let new_type = self.type_overrides.get(&old_guid).map(u32::to_string);
+                            format!("{}{}", key.as_str(), new_type.as_deref().unwrap_or(old_type.as_str()))
+                        }
+                        _ => String::new(),
+                    };
+                    *report.guid_reference_counts.entry(old_guid).or_insert(0) += 1;
+                    format!("{}{}{}{}{}", &caps["prefix"], &caps["open"], new_guid, &caps["close"], type_field)
                 }
+                None => caps[0].to_string(),
             }
-        }
+        });
+        let modified = file_ref_count > 0;
 
         if modified {
-            report.files_with_references.insert(path.to_path_buf());
-            report.total_references_replaced += file_ref_count;
+            let relative_path = self.project_relative(&self.subordinate_project, path);
+            let new_bytes: Cow<[u8]> = match encoding {
+                Some(encoding) => Cow::Owned(encoding.encode(&new_content)),
+                None => Cow::Borrowed(new_content.as_bytes()),
+            };
+            let old_size = encoding.map_or(content.len(), |encoding| encoding.encode(&content).len());
+            report.record_size(&relative_path, old_size, new_bytes.len());
+            // A meta file whose own GUID changed was backed up before that rewrite already
+            let backed_up = is_meta && self.guid_mappings.contains_key(&relative_path);
+            *report.files_with_references.entry(relative_path).or_insert(0) += file_ref_count;
             
-            if dry_run && verbose {
-                println!("  {} {} ({} references)", "[DRY RUN]".cyan(), path.display(), file_ref_count);
-            } else if !dry_run {
-                fs::write(path, new_content)?;
-                if verbose {
-                    println!("  {} {} ({} references)", "Updated references in".green(), path.display(), file_ref_count);
+            if !options.dry_run {
+                if !backed_up {
+                    self.backup_file(path, options, &mut report)?;
+                }
+                if let Err(e) = write_atomic(path, &new_bytes) {
+                    // Leave this file unchanged and carry on with the rest
+                    warn!("Could not write {}: {:#}", path.display(), e);
+                    return Ok(SyncReport {
+                        backed_up_files: report.backed_up_files,
+                        failed_files: vec![(path.to_path_buf(), error_chain(&e))],
+                        ..SyncReport::new()
+                    });
+                }
+            } else {
+                if options.show_diff {
+                    let relative_path = self.project_relative(&self.subordinate_project, path);
+                    let diff = unified_diff(&relative_path, &content, &new_content);
+                    report.diffs.push((relative_path, diff));
+                }
+                if options.shadow_dir.is_some() {
+                    // The meta file's own GUID rewrite already went to the shadow copy; nothing is
+                    // written on a dry run, so apply it again on top of the reference rewrites
+                    let relative_path = self.project_relative(&self.subordinate_project, path);
+                    let shadow_bytes = match self.guid_mappings.get(&relative_path) {
+                        Some((main_guid, _)) if is_meta => MetaFile::rewrite_guid(&new_content, main_guid)
+                            .map_or(new_bytes, |content| Cow::Owned(content.into_bytes())),
+                        _ => new_bytes,
+                    };
+                    self.write_shadow(path, &shadow_bytes, options)?;
                 }
             }
         }
 
-        Ok(())
+        Ok(report)
     }
 
-    pub fn generate_sync_operations_report(&self) -> Result<SyncOperationsReport> {
-        println!("{}", "Generating detailed sync operations report...".bright_blue());
-        
+    /// Build the operations report. With `with_context`, every individual reference is
+    /// recorded with its line number and a snippet of the line, which is slower.
+    pub fn generate_sync_operations_report(&self, with_context: bool) -> Result<SyncOperationsReport> {
         let mut operations = Vec::new();
         
         // First pass: scan all files for references
         let mut guid_references: HashMap<String, Vec<ReferenceUpdate>> = HashMap::new();
+        let mut skipped_files = self.skipped_files();
+        let replaced: HashSet<&str> = self.guid_mappings.values().map(|(_, sub)| sub.as_str()).collect();
+        let prefilter = GuidPrefilter::new(replaced.iter().copied());
+        self.observer.on_references_started(ReferencePass::Count);
         
-        for entry in WalkDir::new(&self.subordinate_project)
-            .into_iter()
-            .filter_map(|e| e.ok())
-        {
-            let path = entry.path();
+        let settings = self.project_settings_files(&self.subordinate_project);
+        for path in self.walk_project(&self.subordinate_project).map(|e| e.into_path()).chain(settings) {
+            let path = path.as_path();
             let ext = path.extension().and_then(|s| s.to_str());
+            if !self.is_selected(path) {
+                continue;
+            }
             
-            if matches!(ext, Some("unity") | Some("prefab") | Some("asset") | Some("mat") | Some("controller")) {
-                // Try to read file as UTF-8, skip if it fails
-                let content = match fs::read_to_string(path) {
+            if path.is_file() && self.is_unity_yaml(path) {
+                self.observer.on_reference_file_read();
+                // Read the file as sync would, skip it if that fails
+                let content = match self.read_text(path) {
                     Ok(content) => content,
                     Err(e) => {
-                        eprintln!("Warning: Could not read {} for report: {}", path.display(), e);
+                        warn!("Could not read {} for report: {}", path.display(), e);
+                        skipped_files.push((path.to_path_buf(), e.to_string()));
                         continue;
                     }
                 };
-                let guid_regex = Regex::new(r"guid:\s*([a-f0-9]{32})")?;
-                let file_id_regex = Regex::new(r"\{fileID:\s*\d+,\s*guid:\s*([a-f0-9]{32}),\s*type:\s*\d+\}")?;
-                
-                let mut file_guid_counts: HashMap<String, usize> = HashMap::new();
-                
-                // Count guid: patterns
-                for cap in guid_regex.captures_iter(&content) {
-                    if let Some(guid) = cap.get(1) {
-                        *file_guid_counts.entry(guid.as_str().to_string()).or_insert(0) += 1;
-                    }
-                }
-                
-                // Count {fileID: ..., guid: ..., type: ...} patterns
-                for cap in file_id_regex.captures_iter(&content) {
-                    if let Some(guid) = cap.get(1) {
-                        *file_guid_counts.entry(guid.as_str().to_string()).or_insert(0) += 1;
-                    }
+                if !prefilter.may_reference(&content) {
+                    continue;
                 }
+                let mut file_guid_details = if with_context {
+                    reference_details(&content)
+                } else {
+                    HashMap::new()
+                };
+                let file_guid_counts = if with_context {
+                    file_guid_details.iter().map(|(guid, details)| (guid.clone(), details.len())).collect()
+                } else {
+                    count_guid_references(&content)
+                };
                 
                 // Add to reference tracking
                 for (guid, count) in file_guid_counts {
                     // Check if this GUID is one we're replacing
-                    if self.guid_mappings.values().any(|(_, sub)| sub == &guid) {
+                    if replaced.contains(guid.as_str()) {
+                        let references = file_guid_details.remove(&guid);
                         let file_type = ext.unwrap_or("unknown").to_string();
-                        let relative_path = path.strip_prefix(&self.subordinate_project)
-                            .unwrap_or(path)
-                            .to_path_buf();
+                        let relative_path = self.project_relative(&self.subordinate_project, path);
                         
-                        guid_references.entry(guid).or_insert_with(Vec::new).push(
+                        guid_references.entry(guid).or_default().push(
                             ReferenceUpdate {
                                 file_path: relative_path,
                                 file_type,
                                 reference_count: count,
+                                references,
                             }
                         );
                     }
@@ -399,67 +2202,416 @@ impl GuidSyncer {
             }
         }
         
-        // Second pass: create sync operations
-        for (rel_path, (main_guid, sub_guid)) in &self.guid_mappings {
+        self.observer.on_references_finished();
+        
+        // Second pass: create sync operations. Meta files sharing a (corrupted, duplicated)
+        // subordinate GUID all get an operation, but the references to that GUID are rewritten
+        // once, so only the first of them lists them.
+        let mut referenced_guids: HashSet<&String> = HashSet::new();
+        for (rel_path, (main_guid, sub_guid)) in self.sorted_mappings() {
+            // Foo.png.meta -> Foo.png; only the trailing .meta is removed
             let asset_name = rel_path
-                .file_stem()
+                .with_extension("")
+                .file_name()
                 .and_then(|s| s.to_str())
                 .unwrap_or("unknown")
-                .to_string()
-                .replace(".meta", "");
+                .to_string();
             
-            let references = guid_references.get(sub_guid).cloned().unwrap_or_default();
+            let asset_importer = MetaFile::parse(&self.subordinate_project.join(rel_path))
+                .ok()
+                .and_then(|info| info.importer)
+                .unwrap_or_default();
+            
+            let references = if referenced_guids.insert(sub_guid) {
+                guid_references.get(sub_guid).cloned().unwrap_or_default()
+            } else {
+                debug!("{} shares GUID {} with another meta file, its references are counted once", rel_path.display(), sub_guid);
+                Vec::new()
+            };
             let total_refs: usize = references.iter().map(|r| r.reference_count).sum();
+            let risk = RiskLevel::assess(&references);
             
             operations.push(SyncOperation {
                 old_guid: sub_guid.clone(),
                 new_guid: main_guid.clone(),
                 asset_path: rel_path.clone(),
                 asset_name,
+                asset_importer,
                 meta_file_update: MetaFileUpdate {
                     path: rel_path.clone(),
                 },
                 reference_updates: references,
                 total_references: total_refs,
+                risk,
+                main_reference_count: None,
             });
         }
         
-        // Sort operations by number of references (most referenced first)
-        operations.sort_by(|a, b| b.total_references.cmp(&a.total_references));
-        
-        let total_files_with_refs: HashSet<PathBuf> = operations
-            .iter()
-            .flat_map(|op| op.reference_updates.iter().map(|r| r.file_path.clone()))
-            .collect();
-        
-        let total_reference_updates: usize = operations
-            .iter()
-            .map(|op| op.total_references)
-            .sum();
+        // Riskiest first, then most referenced first
+        operations.sort_by_key(|op| std::cmp::Reverse((op.risk, op.total_references)));
         
         let report = SyncOperationsReport {
-            summary: SyncSummary {
-                total_guid_differences: operations.len(),
-                total_meta_files_to_update: operations.len(),
-                total_files_with_references: total_files_with_refs.len(),
-                total_reference_updates,
-            },
+            summary: SyncSummary::of(&operations),
             operations,
+            skipped_files,
+            dangling_references: Vec::new(),
+            options: None,
         };
         
         Ok(report)
     }
 
-    pub fn print_summary(&self) {
-        if self.guid_mappings.is_empty() {
-            return;
+    /// The operations report written by `sync --plan` for a sync with `options`. `apply` maps
+    /// each old GUID to a single new one, so an ambiguous subordinate GUID keeps only the
+    /// operation of its first meta file in path order with `AmbiguousResolution::First`, and
+    /// none with `Skip`. Applying the plan gives every meta file sharing it that main GUID.
+    pub fn generate_plan(&self, options: &SyncOptions) -> Result<SyncOperationsReport> {
+        self.check_ambiguous(options)?;
+        let mut plan = self.generate_sync_operations_report(false)?;
+        let kept: HashMap<String, Option<PathBuf>> = self
+            .ambiguous_guids()
+            .into_iter()
+            .map(|ambiguous| {
+                let first = (options.resolve_ambiguous == Some(AmbiguousResolution::First))
+                    .then(|| ambiguous.candidates[0].0.clone());
+                (ambiguous.sub_guid, first)
+            })
+            .collect();
+        plan.operations.retain(|op| match kept.get(&op.old_guid) {
+            Some(first) => first.as_ref() == Some(&op.asset_path),
+            None => true,
+        });
+        plan.summary = SyncSummary::of(&plan.operations);
+        Ok(plan)
+    }
+
+    /// The GUID differences found by the scan, sorted by relative path. Nothing is printed,
+    /// so callers decide how to present them.
+    pub fn diff(&self) -> Vec<GuidDifference> {
+        let mut differences: Vec<GuidDifference> = self
+            .guid_mappings
+            .iter()
+            .map(|(path, (main_guid, sub_guid))| GuidDifference {
+                relative_path: path.clone(),
+                main_guid: main_guid.clone(),
+                sub_guid: sub_guid.clone(),
+                paired_with: self.paired_paths.get(path).cloned(),
+            })
+            .collect();
+        differences.sort_by(|a, b| a.relative_path.cmp(&b.relative_path));
+        differences
+    }
+
+    pub fn scan_result(&self) -> ScanResult {
+        ScanResult {
+            differences: self.diff(),
+            collisions: self.collisions.clone(),
+            ambiguous: self.ambiguous_guids(),
+            orphaned_metas: self.orphaned_metas.clone(),
+            main_only: self.main_only.clone(),
+            sub_only: self.sub_only.clone(),
+        }
+    }
+
+    /// Meta files whose asset file is missing, left out of content matching. They can still
+    /// be paired by path. Only collected with `MatchMode::Content`.
+    pub fn orphaned_metas(&self) -> &[PathBuf] {
+        &self.orphaned_metas
+    }
+
+    /// Main meta files (relative, sorted) with no subordinate counterpart at the same path,
+    /// through the path map or by content: assets the subordinate is missing
+    pub fn main_only(&self) -> &[PathBuf] {
+        &self.main_only
+    }
+
+    /// Subordinate meta files (relative, sorted) that could not be paired with the main project
+    pub fn sub_only(&self) -> &[PathBuf] {
+        &self.sub_only
+    }
+
+    /// Find references in the subordinate's YAML files that still point at a GUID that should
+    /// have been rewritten: either a current difference from the scan, or an old GUID recorded
+    /// in a previous sync report
+    pub fn find_stale_references(&self, previous_sync: Option<&SyncReport>) -> Result<Vec<StaleReference>> {
+        let mut stale_map = self.reverse_guid_map();
+        if let Some(previous_sync) = previous_sync {
+            for change in &previous_sync.guid_changes {
+                stale_map.insert(change.old_guid.as_str(), change.new_guid.as_str());
+            }
+        }
+
+        let mut stale = Vec::new();
+        if stale_map.is_empty() {
+            return Ok(stale);
+        }
+
+        for path in self.reference_candidates() {
+            if !self.is_unity_yaml(&path) {
+                continue;
+            }
+            let content = match self.read_text(&path) {
+                Ok(content) => content,
+                Err(e) => {
+                    warn!("Could not read {}: {}", path.display(), e);
+                    continue;
+                }
+            };
+
+            let relative_path = self.project_relative(&self.subordinate_project, &path);
+            let mut lines = LineCursor::new(&content);
+            for cap in GUID_REGEX.captures_iter(&content) {
+                let guid = cap["guid"].to_ascii_lowercase();
+                if let Some(expected_guid) = stale_map.get(guid.as_str()) {
+                    let (line_number, _) = lines.line_at(cap.name("guid").unwrap().start());
+                    stale.push(StaleReference {
+                        file_path: relative_path.clone(),
+                        line_number,
+                        guid: guid.clone(),
+                        expected_guid: expected_guid.to_string(),
+                    });
+                }
+            }
+        }
+
+        Ok(stale)
+    }
+
+    /// Compare the subordinate project against what a sync report says was written,
+    /// returning every file whose current GUIDs no longer match (path, reason)
+    pub fn detect_drift(&self, report: &SyncReport) -> Result<Vec<(PathBuf, String)>> {
+        let mut drifted = Vec::new();
+
+        for change in &report.guid_changes {
+            let meta_path = self.subordinate_project.join(&change.meta_path);
+            match MetaFile::get_guid_from_file(&meta_path) {
+                Ok(guid) if guid.eq_ignore_ascii_case(&change.new_guid) => {}
+                Ok(guid) => drifted.push((
+                    change.meta_path.clone(),
+                    format!("expected GUID {}, found {}", change.new_guid, guid),
+                )),
+                Err(e) => drifted.push((change.meta_path.clone(), e.to_string())),
+            }
+        }
+
+        let written_guids: HashSet<&str> = report
+            .guid_changes
+            .iter()
+            .map(|change| change.new_guid.as_str())
+            .collect();
+
+        for (rel_path, expected) in &report.files_with_references {
+            let path = self.subordinate_project.join(rel_path);
+            let content = match self.read_text(&path) {
+                Ok(content) => content,
+                Err(e) => {
+                    drifted.push((rel_path.clone(), e.to_string()));
+                    continue;
+                }
+            };
+
+            let found: usize = count_guid_references(&content)
+                .iter()
+                .filter(|(guid, _)| written_guids.contains(guid.as_str()))
+                .map(|(_, count)| count)
+                .sum();
+            if found < *expected {
+                drifted.push((
+                    rel_path.clone(),
+                    format!("expected {} synced references, found {}", expected, found),
+                ));
+            }
+        }
+
+        Ok(drifted)
+    }
+}
+
+/// Count GUID references per GUID in a file's content. References inside
+/// `{fileID: ..., guid: ..., type: ...}` are matched by the `guid:` pattern too,
+/// so each reference is counted exactly once.
+fn count_guid_references(content: &str) -> HashMap<String, usize> {
+    let mut counts: HashMap<String, usize> = HashMap::new();
+
+    for cap in GUID_REGEX.captures_iter(content) {
+        if let Some(guid) = cap.name("guid") {
+            *counts.entry(guid.as_str().to_ascii_lowercase()).or_insert(0) += 1;
+        }
+    }
+
+    counts
+}
+
+/// Maximum length of a context snippet in the operations report
+const CONTEXT_SNIPPET_LEN: usize = 120;
+
+/// Collect every GUID reference in a file's content per GUID, with line number and context
+fn reference_details(content: &str) -> HashMap<String, Vec<ReferenceDetail>> {
+    let mut details: HashMap<String, Vec<ReferenceDetail>> = HashMap::new();
+
+    let mut lines = LineCursor::new(content);
+    for cap in GUID_REGEX.captures_iter(content) {
+        let (line_number, line) = lines.line_at(cap.name("guid").unwrap().start());
+        let snippet = line.trim();
+        let context_snippet = match snippet.char_indices().nth(CONTEXT_SNIPPET_LEN) {
+            Some((end, _)) => format!("{}...", &snippet[..end]),
+            None => snippet.to_string(),
+        };
+        details.entry(cap["guid"].to_ascii_lowercase()).or_default().push(ReferenceDetail {
+            line_number,
+            context_snippet,
+        });
+    }
+
+    details
+}
+
+/// Looks up the lines of matches found in order. Each line is only scanned once, so files with
+/// thousands of references or megabyte-long lines (serialized mesh data) stay linear.
+struct LineCursor<'a> {
+    content: &'a str,
+    /// 1-based number of the current line
+    number: usize,
+    start: usize,
+    /// Offset of the current line's `\n`, or the end of the content
+    end: usize,
+}
+
+impl<'a> LineCursor<'a> {
+    fn new(content: &'a str) -> Self {
+        let end = content.find('\n').unwrap_or(content.len());
+        Self { content, number: 1, start: 0, end }
+    }
+
+    /// The 1-based line number and text of the line containing byte `offset`, which must not
+    /// lie before the previous offset. A reference may wrap across lines, so this is the line
+    /// the GUID itself is on.
+    fn line_at(&mut self, offset: usize) -> (usize, &'a str) {
+        while offset > self.end {
+            self.start = self.end + 1;
+            self.number += 1;
+            self.end = self.content[self.start..].find('\n').map_or(self.content.len(), |i| self.start + i);
+        }
+        (self.number, self.content[self.start..self.end].trim_end_matches('\r'))
+    }
+}
+
+/// Write a report as pretty-printed JSON
+fn write_report_json(report: &impl Serialize, path: &Path) -> Result<()> {
+    let json = serde_json::to_string_pretty(report).map_err(|source| GuidSyncError::Json {
+        message: "Failed to serialize report".to_string(),
+        source,
+    })?;
+    fs::write(path, json).io_context(|| format!("Failed to write report: {}", path.display()))
+}
+
+fn modified_time(path: &Path) -> Result<std::time::SystemTime> {
+    fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .io_context(|| format!("Failed to read modification time: {}", path.display()))
+}
+
+/// Assembly definitions and references, which are JSON rather than YAML
+fn is_json_asset(path: &Path) -> bool {
+    matches!(path.extension().and_then(|s| s.to_str()), Some("asmdef") | Some("asmref"))
+}
+
+fn is_meta_file(path: &Path) -> bool {
+    path.extension().and_then(|s| s.to_str()) == Some("meta")
+}
+
+/// Check if a file is likely a Unity YAML file by checking its first line. With
+/// `try_encodings` the line may also be UTF-16 or Latin-1.
+fn has_yaml_header(path: &Path, try_encodings: bool) -> bool {
+    let Ok(file) = fs::File::open(path) else {
+        return false;
+    };
+    let first_line = if try_encodings {
+        let mut head = Vec::new();
+        if file.take(YAML_HEADER_SNIFF_LENGTH).read_to_end(&mut head).is_err() {
+            return false;
+        }
+        TextEncoding::detect(&head).decode_lossy(&head).lines().next().map(str::to_string)
+    } else {
+        BufReader::new(file).lines().next().and_then(|line| line.ok())
+    };
+    match first_line {
+        // Unity YAML files typically start with %YAML (possibly after a UTF-8 BOM)
+        Some(first_line) => {
+            let first_line = first_line.trim_start_matches('\u{feff}');
+            first_line.starts_with("%YAML") || first_line.starts_with("---")
+        }
+        None => false,
+    }
+}
+
+/// Bytes read to find the first line of a file that may not be UTF-8
+const YAML_HEADER_SNIFF_LENGTH: u64 = 256;
+
+/// Whether a file starts with zero bytes, like Unity's binary serialized assets
+fn looks_binary(path: &Path) -> bool {
+    let Ok(file) = fs::File::open(path) else {
+        return false;
+    };
+    let mut head = Vec::new();
+    file.take(YAML_HEADER_SNIFF_LENGTH).read_to_end(&mut head).is_ok() && head.contains(&0)
+}
+
+/// Hash the asset file behind every meta in `metas` that has no counterpart in `others`,
+/// grouping meta paths by SHA-256 of the content. Folders are skipped, and metas without an
+/// asset file are added to `orphaned` (relative to the project) instead.
+fn hash_unmatched_assets(
+    project_path: &Path,
+    metas: &HashMap<PathBuf, String>,
+    others: &HashMap<PathBuf, String>,
+    orphaned: &mut Vec<PathBuf>,
+) -> HashMap<[u8; 32], Vec<PathBuf>> {
+    let mut by_hash: HashMap<[u8; 32], Vec<PathBuf>> = HashMap::new();
+
+    for rel_path in metas.keys().filter(|p| !others.contains_key(*p)) {
+        // Foo.png.meta -> Foo.png
+        let asset_path = project_path.join(rel_path).with_extension("");
+        if !asset_path.exists() {
+            orphaned.push(rel_path.clone());
+            continue;
+        }
+        if !asset_path.is_file() {
+            continue;
+        }
+        match fs::read(&asset_path) {
+            Ok(bytes) => {
+                by_hash.entry(Sha256::digest(&bytes).into()).or_default().push(rel_path.clone());
+            }
+            Err(e) => {
+                warn!("Could not hash {}: {}", asset_path.display(), e);
+            }
         }
+    }
+
+    by_hash
+}
 
-        println!("\n{}", "GUID Mapping Summary:".bright_white().underline());
-        for (path, (main_guid, sub_guid)) in &self.guid_mappings {
-            println!("  {}", path.display().to_string().bright_cyan());
-            println!("    {} {}", "Main:".green(), main_guid);
-            println!("    {} {}", "Sub: ".red(), sub_guid);
+/// Whether two paths name the same project, with or without its Assets folder
+fn same_project(a: &Path, b: &Path) -> bool {
+    let normalize = |path: &Path| {
+        let path = canonical(path);
+        match path.file_name() {
+            Some(name) if name == "Assets" => path.parent().map_or(path.clone(), Path::to_path_buf),
+            _ => path,
         }
+    };
+    normalize(a) == normalize(b)
+}
+
+/// The real path behind `path`, or `path` itself if it cannot be resolved
+fn canonical(path: &Path) -> PathBuf {
+    path.canonicalize().unwrap_or_else(|_| path.to_path_buf())
+}
+
+/// Whether the assets behind two meta files have the same bytes
+fn same_asset_contents(main_meta: &Path, sub_meta: &Path) -> bool {
+    match (fs::read(main_meta.with_extension("")), fs::read(sub_meta.with_extension(""))) {
+        (Ok(main), Ok(sub)) => main == sub,
+        _ => false,
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,32 @@
+//! Keep Unity asset GUIDs in sync between two projects.
+//!
+//! [`GuidSyncer`] scans a main and a subordinate project, pairs their meta files and rewrites
+//! the subordinate's GUIDs (meta files and every reference to them) to match the main project.
+//! [`MetaFile`] reads and updates single meta files without parsing the YAML.
+
+mod atomic_write;
+pub mod asset_database;
+pub mod checkpoint;
+pub mod config;
+mod diff;
+pub mod editor_settings;
+pub mod error;
+pub mod git_commit;
+pub mod guid_mapper;
+pub mod mapping_file;
+pub mod meta_parser;
+pub mod observer;
+pub mod path_filter;
+pub mod project_archive;
+pub mod report_diff;
+pub mod report_format;
+pub mod review_tui;
+pub mod scan_cache;
+mod slash_path;
+mod text_encoding;
+pub mod theme;
+
+pub use error::GuidSyncError;
+pub use guid_mapper::{GuidDifference, GuidSyncer, ScanOptions, SyncOperationsReport, SyncOptions, SyncReport};
+pub use meta_parser::{MetaFile, MetaInfo};
+pub use observer::SyncObserver;
@@ -1,25 +1,113 @@
-mod guid_mapper;
-mod meta_parser;
-
-use anyhow::Result;
-use clap::{Parser, Subcommand};
+use anyhow::{Context, Result};
+use clap::{Args, CommandFactory, FromArgMatches, Parser, Subcommand, ValueEnum};
 use colored::*;
+use std::collections::{HashMap, HashSet};
 use std::fs;
-use std::path::PathBuf;
+use std::io::{IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
-use guid_mapper::GuidSyncer;
+use guid_sync::guid_mapper::{top_limit, AmbiguousResolution, DanglingReference, CombinedSyncReport, GuidChange, GuidSyncer, DEFAULT_REFERENCE_EXTENSIONS, DEFAULT_TOP, MatchMode, ScanOptions, SyncDirection, SyncOptions, SyncReport};
+use guid_sync::asset_database::MainGuidSource;
+use guid_sync::config::{self, Config};
+use guid_sync::git_commit;
+use guid_sync::mapping_file::{self, PlanOptions};
+use guid_sync::path_filter::PathFilter;
+use guid_sync::project_archive::{self, ProjectArchive};
+use guid_sync::report_diff::ReportDiff;
+use guid_sync::report_format::ReportFormat;
+use guid_sync::review_tui;
+use guid_sync::theme::{self, Theme};
+use guid_sync::observer::ReferencePass;
+use guid_sync::{MetaFile, SyncObserver, SyncOperationsReport};
+use indicatif::{ProgressBar, ProgressStyle};
+use notify_debouncer_mini::new_debouncer;
+use notify_debouncer_mini::notify::RecursiveMode;
+use tracing::level_filters::LevelFilter;
 
 #[derive(Parser)]
 #[command(name = "guid-sync")]
 #[command(about = "Unity GUID synchronization tool", long_about = None)]
 struct Cli {
+    /// Disable colored output (also honored via the NO_COLOR environment variable)
+    #[arg(long, global = true)]
+    no_color: bool,
+
+    /// How old and new GUIDs are told apart: by color, by -/+ markers too, or not at all
+    #[arg(long, value_enum, default_value_t = Theme::Color, global = true)]
+    theme: Theme,
+
+    /// Log every file that is updated (-v), or also debug details (-vv)
+    #[arg(short, long, action = clap::ArgAction::Count, global = true)]
+    verbose: u8,
+
+    /// Print nothing on stdout but the output asked for (JSON, --summary-only, --show-diff, ...)
+    #[arg(short, long, global = true, conflicts_with = "verbose")]
+    quiet: bool,
+
+    /// Read default options from this file instead of ./guid-sync.toml
+    #[arg(long, value_name = "FILE", global = true)]
+    config: Option<PathBuf>,
+
     #[command(subcommand)]
     command: Commands,
 }
 
+#[derive(Args)]
+struct FilterArgs {
+    /// Only process assets matching this glob, relative to Assets (repeatable)
+    #[arg(long)]
+    include: Vec<String>,
+    
+    /// Skip assets matching this glob, relative to Assets (repeatable, wins over --include)
+    #[arg(long)]
+    exclude: Vec<String>,
+    
+    /// Also scan embedded packages in the Packages folder next to Assets
+    #[arg(long)]
+    include_packages: bool,
+    
+    /// Also update references in ProjectSettings/*.asset, e.g. a render pipeline in GraphicsSettings
+    #[arg(long)]
+    include_project_settings: bool,
+    
+    /// Look for references in files with this extension instead of the built-in list (repeatable)
+    #[arg(long = "ref-ext", value_name = "EXT")]
+    ref_ext: Vec<String>,
+    
+    /// Descend into symlinked folders, processing each real file once
+    #[arg(long)]
+    follow_symlinks: bool,
+    
+    /// Read reference files that are not UTF-8 as UTF-16 or Latin-1, and write them back so
+    #[arg(long)]
+    try_encodings: bool,
+}
+
+impl FilterArgs {
+    fn to_filter(&self) -> Result<PathFilter> {
+        Ok(PathFilter::new(&self.include, &self.exclude)?)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum OutputFormat {
+    /// Colored human-readable output
+    Text,
+    /// JSON on stdout
+    Json,
+}
+
 #[derive(Subcommand)]
+#[allow(clippy::large_enum_variant)] // parsed once per run, boxing the sync options would only clutter the match
 enum Commands {
     /// Scan projects and show GUID differences
+    ///
+    /// Exit codes: 0 = in sync, 1 = error, 2 = differences found
     Scan {
         /// Path to the main Unity project (GUIDs from this project will be preserved)
         #[arg(short, long)]
@@ -28,6 +116,61 @@ enum Commands {
         /// Path to the subordinate Unity project (GUIDs will be updated to match main)
         #[arg(short, long)]
         subordinate: PathBuf,
+        
+        /// How to pair assets between the two projects
+        #[arg(long, value_enum, default_value_t = MatchMode::Path)]
+        match_by: MatchMode,
+        
+        /// JSON object of subordinate -> main paths (relative to Assets) for assets that moved
+        #[arg(long, value_name = "FILE")]
+        path_map: Option<PathBuf>,
+        
+        /// Leave folder GUIDs alone; renaming them can affect more than file GUIDs
+        #[arg(long)]
+        skip_folders: bool,
+        
+        /// Fail instead of skipping meta or YAML files that cannot be read or parsed
+        #[arg(long)]
+        fail_on_warning: bool,
+        
+        /// Compare subordinate meta files as they are read, to keep memory low on huge projects
+        #[arg(long, conflicts_with = "match_by")]
+        low_memory: bool,
+        
+        /// Cache the main project scan in this file and only re-read meta files changed since
+        #[arg(long)]
+        cache: Option<PathBuf>,
+        
+        /// `db` cross-checks the main meta GUIDs against Unity's imported asset database in Library
+        #[arg(long, value_enum, default_value_t = MainGuidSource::Meta)]
+        main_guid_source: MainGuidSource,
+        
+        /// Only show differences whose main or subordinate GUID starts with these hex characters
+        #[arg(long, value_name = "HEX", value_parser = parse_guid_prefix)]
+        guid_prefix: Option<String>,
+        
+        #[command(flatten)]
+        filter: FilterArgs,
+        
+        /// Exit with 0 even when differences are found
+        #[arg(long)]
+        exit_zero: bool,
+        
+        /// Output format
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+        format: OutputFormat,
+        
+        /// Show how many references each differing GUID has in the summary, and list the unreferenced ones
+        #[arg(long, conflicts_with_all = ["format", "summary_only"])]
+        with_counts: bool,
+        
+        /// Print a single `guid-sync: N differences, N meta files, N references` line and nothing else
+        #[arg(long, conflicts_with = "format")]
+        summary_only: bool,
+        
+        /// Browse the planned operations in an interactive terminal UI instead of printing them
+        #[arg(long, conflicts_with_all = ["format", "with_counts", "summary_only"])]
+        tui: bool,
     },
     
     /// Generate detailed sync operations report
@@ -40,9 +183,57 @@ enum Commands {
         #[arg(short, long)]
         subordinate: PathBuf,
         
-        /// Output file for the report (JSON format)
-        #[arg(short, long)]
-        output: PathBuf,
+        /// Output file for the report, or `-` to write it to stdout
+        #[arg(short, long, required_unless_present = "output_dir", conflicts_with = "output_dir")]
+        output: Option<PathBuf>,
+        
+        /// Write the report into this folder under a timestamped name instead, creating it if needed
+        #[arg(long, value_name = "DIR")]
+        output_dir: Option<PathBuf>,
+        
+        /// Report file format
+        #[arg(long, value_enum, default_value_t = ReportFormat::Json)]
+        format: ReportFormat,
+        
+        /// Record the line number and a snippet of context for every reference (slower)
+        #[arg(long)]
+        with_context: bool,
+        
+        /// Number of top operations listed on the console, 0 for all
+        #[arg(long, value_name = "N", default_value_t = DEFAULT_TOP)]
+        top: usize,
+        
+        /// Only report operations whose old or new GUID starts with these hex characters
+        #[arg(long, value_name = "HEX", value_parser = parse_guid_prefix)]
+        guid_prefix: Option<String>,
+        
+        /// After writing the report, browse it in an interactive terminal UI
+        #[arg(long)]
+        tui: bool,
+        
+        /// Also count each asset's references in the main project, to compare sync directions
+        #[arg(long)]
+        scan_main_refs: bool,
+        
+        /// Also list GUIDs referenced in the subordinate that no meta file in either project has
+        #[arg(long)]
+        report_dangling: bool,
+        
+        #[command(flatten)]
+        filter: FilterArgs,
+    },
+    
+    /// Compare two JSON operations reports: operations added, removed or with changed references
+    ReportDiff {
+        /// The earlier report, written by `report --format json`
+        old: PathBuf,
+        
+        /// The later report
+        new: PathBuf,
+        
+        /// Output format
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+        format: OutputFormat,
     },
     
     /// Synchronize GUIDs from main project to subordinate project
@@ -51,46 +242,635 @@ enum Commands {
         #[arg(short, long)]
         main: PathBuf,
         
+        /// Path to a subordinate Unity project (GUIDs will be updated to match main); repeatable.
+        /// A single scene, prefab or asset file inside a project's Assets folder only has its
+        /// own references rewritten.
+        #[arg(short, long, required = true)]
+        subordinate: Vec<PathBuf>,
+        
+        /// Perform a dry run without making changes
+        ///
+        /// When the GUID_SYNC_SAFE environment variable is set, every sync is a dry run unless
+        /// --apply is given, whether or not --dry-run is passed.
+        #[arg(short, long)]
+        dry_run: bool,
+        
+        /// Write the changes; required for a live sync when GUID_SYNC_SAFE is set
+        ///
+        /// Precedence: --apply and --dry-run cannot be combined. With GUID_SYNC_SAFE set, only
+        /// --apply writes. Without it, every sync writes unless --dry-run is given.
+        #[arg(long, conflicts_with = "dry_run")]
+        apply: bool,
+        
+        /// Ask for every asset whether its GUID should be synced
+        #[arg(short, long, conflicts_with = "direction")]
+        interactive: bool,
+        
+        /// Don't ask for confirmation before modifying files
+        #[arg(short, long)]
+        yes: bool,
+        
+        /// Leave out assets whose old GUID is not referenced anywhere (only their meta file would change)
+        #[arg(long)]
+        skip_unreferenced: bool,
+        
+        /// Only sync the asset whose subordinate or main GUID is this one (repeatable)
+        #[arg(long = "only-guid", value_name = "GUID", value_parser = parse_guid)]
+        only_guid: Vec<String>,
+        
+        /// Only sync assets that changed in the main project's git repository since this commit
+        #[arg(long, value_name = "REF")]
+        since: Option<String>,
+        
+        /// With --dry-run, print a unified diff of every line that would change
+        #[arg(long, requires = "dry_run")]
+        show_diff: bool,
+        
+        /// Also rewrite GUID references embedded in meta files, leaving their own GUID alone
+        #[arg(long)]
+        rewrite_meta_refs: bool,
+        
+        /// Only rewrite the meta files' own GUIDs, not the references to them
+        #[arg(long, conflicts_with = "refs_only")]
+        meta_only: bool,
+        
+        /// Only rewrite references, leaving the meta files' GUIDs unchanged
+        #[arg(long)]
+        refs_only: bool,
+        
+        /// Commit the files the sync changed in each project's git repository
+        #[arg(long, conflicts_with = "dry_run")]
+        commit: bool,
+        
+        /// Record every file a live sync finishes in this file, so an interrupted sync can be
+        /// continued with --resume; it is deleted once the sync completes
+        #[arg(long, value_name = "FILE", conflicts_with_all = ["dry_run", "resume"])]
+        checkpoint: Option<PathBuf>,
+        
+        /// Continue an interrupted sync from its checkpoint, skipping the files already done
+        #[arg(long, value_name = "FILE", conflicts_with = "dry_run")]
+        resume: Option<PathBuf>,
+        
+        /// Abort a live sync that would rewrite more than N references in total
+        #[arg(long, value_name = "N")]
+        max_references: Option<usize>,
+        
+        /// With --dry-run, write the planned operations as JSON for review and `apply`
+        #[arg(long, value_name = "FILE", requires = "dry_run", conflicts_with = "direction")]
+        plan: Option<PathBuf>,
+        
+        /// With --dry-run, write the new contents of every file that would change into this
+        /// directory (same paths as in the project), to compare with external diff tools
+        #[arg(long, value_name = "DIR", requires = "dry_run")]
+        shadow_out: Option<PathBuf>,
+        
+        /// Stop printing diffs after this many lines
+        #[arg(long, default_value_t = 500, requires = "show_diff")]
+        max_diff_lines: usize,
+        
+        /// Number of most referenced GUIDs in the dry run report, 0 for all
+        #[arg(long, value_name = "N", default_value_t = DEFAULT_TOP)]
+        top: usize,
+        
+        /// Export detailed report to a JSON file
+        #[arg(short = 'r', long)]
+        report: Option<PathBuf>,
+        
+        /// Copy every file into this directory before modifying it
+        #[arg(short, long)]
+        backup: Option<PathBuf>,
+        
+        /// Allow writing into an existing backup directory
+        #[arg(long, requires = "backup")]
+        force: bool,
+        
+        /// Maximum number of threads used to update references (defaults to one per core)
+        #[arg(short, long)]
+        jobs: Option<usize>,
+        
+        /// Sync even if a main GUID is already used by a different subordinate asset
+        #[arg(long)]
+        allow_collisions: bool,
+        
+        /// How to sync a subordinate GUID shared by meta files that map to different main GUIDs;
+        /// without it such a sync is refused
+        #[arg(long, value_enum, value_name = "MODE")]
+        resolve_ambiguous: Option<AmbiguousResolution>,
+        
+        /// How to pair assets between the two projects
+        #[arg(long, value_enum, default_value_t = MatchMode::Path)]
+        match_by: MatchMode,
+        
+        /// JSON object of subordinate -> main paths (relative to Assets) for assets that moved
+        #[arg(long, value_name = "FILE")]
+        path_map: Option<PathBuf>,
+        
+        /// Leave folder GUIDs alone; renaming them can affect more than file GUIDs
+        #[arg(long)]
+        skip_folders: bool,
+        
+        /// Fail instead of skipping meta or YAML files that cannot be read or parsed
+        #[arg(long)]
+        fail_on_warning: bool,
+        
+        /// Compare subordinate meta files as they are read, to keep memory low on huge projects
+        #[arg(long, conflicts_with = "match_by")]
+        low_memory: bool,
+        
+        /// Cache the main project scan in this file and only re-read meta files changed since
+        #[arg(long)]
+        cache: Option<PathBuf>,
+        
+        /// `db` cross-checks the main meta GUIDs against Unity's imported asset database in Library
+        #[arg(long, value_enum, default_value_t = MainGuidSource::Meta)]
+        main_guid_source: MainGuidSource,
+        
+        /// Which project keeps its GUID when the two disagree; the other one is rewritten
+        #[arg(long, value_enum, default_value_t = SyncDirection::MainWins)]
+        direction: SyncDirection,
+        
+        #[command(flatten)]
+        filter: FilterArgs,
+    },
+    
+    /// Check that no subordinate reference still points at a GUID that should have been synced
+    ///
+    /// Exit codes: 0 = no stale references, 1 = error, 2 = stale references found
+    Verify {
+        /// Path to the main Unity project (GUIDs from this project will be preserved)
+        #[arg(short, long)]
+        main: PathBuf,
+        
         /// Path to the subordinate Unity project (GUIDs will be updated to match main)
         #[arg(short, long)]
         subordinate: PathBuf,
         
-        /// Perform a dry run without making changes
+        /// Report exported by `sync --report` (also a combined one), to also catch references to GUIDs it replaced
         #[arg(short, long)]
-        dry_run: bool,
+        report: Option<PathBuf>,
+        
+        #[command(flatten)]
+        filter: FilterArgs,
+    },
+    
+    /// Apply an explicit old -> new GUID mapping file to a subordinate project
+    Apply {
+        /// Path to the Unity project to update
+        #[arg(short, long)]
+        subordinate: PathBuf,
         
-        /// Verbose output - show all file updates
+        /// JSON array of {"old_guid", "new_guid"} objects, or a CSV file with old_guid,new_guid rows
+        #[arg(short = 'p', long)]
+        mapping: PathBuf,
+        
+        /// Perform a dry run without making changes
         #[arg(short, long)]
-        verbose: bool,
+        dry_run: bool,
         
         /// Export detailed report to a JSON file
         #[arg(short = 'r', long)]
         report: Option<PathBuf>,
+        
+        /// Apply even if a new GUID is already used by a different asset
+        #[arg(long)]
+        allow_collisions: bool,
+        
+        /// Don't ask for confirmation before modifying files
+        #[arg(short, long)]
+        yes: bool,
+    },
+    
+    /// Check a single project for GUIDs shared by more than one meta file
+    ///
+    /// Exit codes: 0 = no duplicates, 1 = error, 2 = duplicate GUIDs found
+    Doctor {
+        /// Path to the Unity project to check
+        #[arg(short, long)]
+        project: PathBuf,
+        
+        #[command(flatten)]
+        filter: FilterArgs,
+    },
+    
+    /// Summarize a single project: meta files, GUIDs, reference files and most referenced GUIDs
+    Stats {
+        /// Path to the Unity project to summarize
+        #[arg(short, long)]
+        project: PathBuf,
+        
+        /// Output format
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+        format: OutputFormat,
+        
+        #[command(flatten)]
+        filter: FilterArgs,
+    },
+    
+    /// Reverse a previous sync using its exported report
+    Undo {
+        /// Report exported by `sync --report`, also one combining several projects
+        #[arg(short, long)]
+        report: PathBuf,
+        
+        /// Path to the subordinate Unity project the report was produced for
+        #[arg(short, long)]
+        subordinate: PathBuf,
+        
+        /// Perform a dry run without making changes
+        #[arg(short, long)]
+        dry_run: bool,
+        
+        /// Don't ask for confirmation before modifying files
+        #[arg(short, long)]
+        yes: bool,
+    },
+    
+    /// Keep a subordinate project in sync while the main project's meta files change
+    Watch {
+        /// Path to the main Unity project (GUIDs from this project will be preserved)
+        #[arg(short, long)]
+        main: PathBuf,
+        
+        /// Path to the subordinate Unity project (GUIDs will be updated to match main)
+        #[arg(short, long)]
+        subordinate: PathBuf,
+        
+        /// Sync even if a main GUID is already used by a different subordinate asset
+        #[arg(long)]
+        allow_collisions: bool,
+        
+        /// Don't ask for confirmation before modifying files
+        #[arg(short, long)]
+        yes: bool,
+        
+        #[command(flatten)]
+        filter: FilterArgs,
+    },
+    
+    /// Write a starter guid-sync.toml with default options to the working directory
+    Init {
+        /// Main project to record in the config
+        #[arg(short, long)]
+        main: Option<PathBuf>,
+        
+        /// Subordinate project to record in the config
+        #[arg(short, long)]
+        subordinate: Option<PathBuf>,
+        
+        /// Overwrite an existing guid-sync.toml
+        #[arg(long)]
+        force: bool,
     },
 }
 
-fn main() -> Result<()> {
-    let cli = Cli::parse();
+/// Environment variable that makes `sync` write only with --apply
+const SAFE_MODE_ENV: &str = "GUID_SYNC_SAFE";
+
+/// Exit code returned by `scan`, `verify` and `doctor` when problems were found
+const EXIT_DIFFERENCES_FOUND: u8 = 2;
+
+fn main() -> ExitCode {
+    // The config file has to be known before parsing, its values become the flags' defaults
+    let config = match Config::discover(config_arg().as_deref()) {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("Error: {:?}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+    let matches = with_config_defaults(Cli::command(), &config).get_matches();
+    let cli = Cli::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+    // NO_COLOR is honored when set to any non-empty value, see https://no-color.org
+    let no_color_env = std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty());
+    if cli.no_color || no_color_env {
+        colored::control::set_override(false);
+    }
+    theme::set(cli.theme);
+    QUIET.store(cli.quiet, Ordering::Relaxed);
+    init_logging(cli.verbose, !(cli.no_color || no_color_env) && std::io::stderr().is_terminal());
     
+    match run(cli) {
+        Ok(code) => code,
+        Err(e) => {
+            eprintln!("Error: {:?}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// The `--config` value, looked up in the raw arguments ahead of the real parse
+fn config_arg() -> Option<PathBuf> {
+    let mut args = std::env::args_os().skip(1).take_while(|arg| arg != "--");
+    while let Some(arg) = args.next() {
+        if arg == "--config" {
+            return args.next().map(PathBuf::from);
+        }
+        if let Some(path) = arg.to_str().and_then(|arg| arg.strip_prefix("--config=")) {
+            return Some(PathBuf::from(path));
+        }
+    }
+    None
+}
+
+/// Subcommands that take the config file's `main` and `subordinate` as defaults. Others with
+/// those flags, like `init`, only use what is given on the command line.
+const PROJECT_DEFAULT_COMMANDS: [&str; 3] = ["scan", "report", "sync"];
+
+/// Make the config file's values the defaults of the matching flags of every subcommand, so
+/// flags given on the command line still win over them
+fn with_config_defaults(mut command: clap::Command, config: &Config) -> clap::Command {
+    let direction = config
+        .direction
+        .and_then(|direction| direction.to_possible_value())
+        .map(|value| value.get_name().to_string());
+    let defaults: Vec<(&str, Vec<String>)> = vec![
+        ("main", config.main.iter().map(|path| path.to_string_lossy().into_owned()).collect()),
+        ("subordinate", config.subordinate.iter().map(|path| path.to_string_lossy().into_owned()).collect()),
+        ("include", config.include.clone()),
+        ("exclude", config.exclude.clone()),
+        ("ref_ext", config.ref_ext.clone()),
+        ("direction", direction.into_iter().collect()),
+    ];
+    let subcommands: Vec<String> = command.get_subcommands().map(|sub| sub.get_name().to_string()).collect();
+    for name in subcommands {
+        command = command.mut_subcommand(&name, |mut sub| {
+            for (id, values) in &defaults {
+                if values.is_empty() || !sub.get_arguments().any(|arg| arg.get_id() == id) {
+                    continue;
+                }
+                if matches!(*id, "main" | "subordinate") && !PROJECT_DEFAULT_COMMANDS.contains(&name.as_str()) {
+                    continue;
+                }
+                let values = values.clone();
+                sub = sub.mut_arg(id, |arg| arg.required(false).default_values(values));
+            }
+            sub
+        });
+    }
+    command
+}
+
+/// Send log events to stderr: warnings by default, per-file updates with -v, debug details with -vv
+fn init_logging(verbosity: u8, ansi: bool) {
+    let level = match verbosity {
+        0 => LevelFilter::WARN,
+        1 => LevelFilter::INFO,
+        _ => LevelFilter::DEBUG,
+    };
+    tracing_subscriber::fmt()
+        .with_max_level(level)
+        .with_writer(|| LogWriter)
+        .with_ansi(ansi)
+        .with_target(false)
+        .without_time()
+        .init();
+}
+
+/// Whether GUID_SYNC_SAFE is set (to anything but empty or `0`), making dry runs the default
+fn safe_mode() -> bool {
+    std::env::var_os(SAFE_MODE_ENV).is_some_and(|value| !value.is_empty() && value != "0")
+}
+
+/// Whether -v was given, for the human-facing output that goes with the per-file log
+fn verbose() -> bool {
+    LevelFilter::current() >= LevelFilter::INFO
+}
+
+/// Set from --quiet before any command runs
+static QUIET: AtomicBool = AtomicBool::new(false);
+
+/// Whether --quiet was given, silencing banners, findings and status lines on stdout
+fn quiet() -> bool {
+    QUIET.load(Ordering::Relaxed)
+}
+
+/// `println!` for informational output, which --quiet silences
+macro_rules! status {
+    ($($arg:tt)*) => {
+        if !quiet() {
+            println!($($arg)*);
+        }
+    };
+}
+
+fn run(cli: Cli) -> Result<ExitCode> {
     match cli.command {
-        Commands::Scan { main, subordinate } => {
+        Commands::Scan { main, subordinate, match_by, path_map, skip_folders, fail_on_warning, low_memory, cache, main_guid_source, guid_prefix, filter, exit_zero, format, with_counts, summary_only, tui } => {
+            let scan_options = ScanOptions {
+                match_by,
+                filter: filter.to_filter()?,
+                include_packages: filter.include_packages,
+                include_project_settings: filter.include_project_settings,
+                skip_folders,
+                fail_on_warning,
+                low_memory,
+                reference_extensions: filter.ref_ext.clone(),
+                follow_symlinks: filter.follow_symlinks,
+                try_encodings: filter.try_encodings,
+                path_map: load_path_map(path_map.as_deref())?,
+                guid_prefix,
+                main_guid_source,
+            };
+            scan_projects(main, subordinate, scan_options, cache, exit_zero, ScanOutput { format, with_counts, summary_only, tui })
+        }
+        Commands::Report { main, subordinate, output, output_dir, format, with_context, top, guid_prefix, tui, scan_main_refs, report_dangling, filter } => {
             validate_paths(&main, &subordinate)?;
-            scan_projects(main, subordinate)?;
+            let output = match (output, output_dir) {
+                (Some(output), _) => output,
+                (None, Some(dir)) => timestamped_report_path(&dir, format)?,
+                (None, None) => unreachable!("clap requires --output or --output-dir"),
+            };
+            let scan_options = ScanOptions { filter: filter.to_filter()?, include_packages: filter.include_packages, include_project_settings: filter.include_project_settings, reference_extensions: filter.ref_ext.clone(), follow_symlinks: filter.follow_symlinks, try_encodings: filter.try_encodings, guid_prefix, ..Default::default() };
+            generate_operations_report(main, subordinate, scan_options, ReportOutput { path: output, format, with_context, top, tui, scan_main_refs, report_dangling })
+        }
+        Commands::ReportDiff { old, new, format } => {
+            let diff = ReportDiff::between(
+                &SyncOperationsReport::load_from_file(&old)?,
+                &SyncOperationsReport::load_from_file(&new)?,
+            );
+            match format {
+                OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&diff)?),
+                OutputFormat::Text => diff.print(),
+            }
+            Ok(ExitCode::SUCCESS)
         }
-        Commands::Report { main, subordinate, output } => {
+        Commands::Sync { main, mut subordinate, dry_run, apply, interactive, yes, skip_unreferenced, only_guid, since, show_diff, rewrite_meta_refs, meta_only, refs_only, commit, checkpoint, resume, max_references, plan, shadow_out, max_diff_lines, top, report, backup, force, jobs, allow_collisions, resolve_ambiguous, match_by, path_map, skip_folders, fail_on_warning, low_memory, cache, main_guid_source, direction, filter } => {
+            let only_file = match subordinate.as_slice() {
+                [file] if file.is_file() => {
+                    if meta_only || rewrite_meta_refs || plan.is_some() || checkpoint.is_some() || resume.is_some() || direction != SyncDirection::MainWins {
+                        anyhow::bail!("A single subordinate file only has its references rewritten; --meta-only, --rewrite-meta-refs, --plan, --checkpoint, --resume and --direction cannot be used with it");
+                    }
+                    let file = file.canonicalize()?;
+                    subordinate = vec![file_project(&file)?];
+                    Some(file)
+                }
+                _ => None,
+            };
+            if let Some(file) = subordinate.iter().find(|sub| sub.is_file()) {
+                anyhow::bail!("{} is a file; a single file can only be synced as the only --subordinate", file.display());
+            }
+            for sub in &subordinate {
+                validate_paths(&main, sub)?;
+            }
+            if direction != SyncDirection::MainWins && subordinate.len() > 1 {
+                anyhow::bail!("--direction other than main-wins can only be used with a single subordinate project");
+            }
+            if plan.is_some() && subordinate.len() > 1 {
+                anyhow::bail!("--plan can only be used with a single subordinate project");
+            }
+            if (checkpoint.is_some() || resume.is_some()) && (subordinate.len() > 1 || direction != SyncDirection::MainWins) {
+                anyhow::bail!("--checkpoint and --resume can only be used with a single subordinate project and --direction main-wins");
+            }
+            if let Some(checkpoint) = checkpoint.as_ref().filter(|path| path.exists()) {
+                anyhow::bail!("Checkpoint {} already exists; continue that sync with --resume or delete it", checkpoint.display());
+            }
+            if let Some(resume) = resume.as_ref().filter(|path| !path.exists()) {
+                anyhow::bail!("Checkpoint {} does not exist", resume.display());
+            }
+            let dry_run = if safe_mode() {
+                if !apply {
+                    status!("{}", "GUID_SYNC_SAFE is set: running as a dry run, pass --apply to write changes".bright_cyan());
+                }
+                !apply
+            } else {
+                dry_run
+            };
+            if commit && dry_run {
+                anyhow::bail!("--commit needs a live sync; pass --apply while GUID_SYNC_SAFE is set");
+            }
+            let options = SyncOptions {
+                dry_run,
+                backup_dir: backup,
+                force,
+                jobs,
+                allow_collisions,
+                show_diff,
+                max_diff_lines,
+                rewrite_meta_refs,
+                top,
+                shadow_dir: shadow_out,
+                checkpoint: checkpoint.or_else(|| resume.clone()),
+                meta_only,
+                refs_only,
+                only_file,
+                resolve_ambiguous,
+            };
+            let scan_options = ScanOptions { match_by, filter: filter.to_filter()?, include_packages: filter.include_packages, include_project_settings: filter.include_project_settings, reference_extensions: filter.ref_ext.clone(), follow_symlinks: filter.follow_symlinks, skip_folders, fail_on_warning, low_memory, try_encodings: filter.try_encodings, path_map: load_path_map(path_map.as_deref())?, main_guid_source, ..Default::default() };
+            if let Some(file) = options.only_file.as_ref().filter(|file| !scan_options.is_reference_file(file)) {
+                anyhow::bail!("{} is not a file whose references are rewritten; see --ref-ext", file.display());
+            }
+            let plan_options = PlanOptions {
+                include: filter.include.clone(),
+                exclude: filter.exclude.clone(),
+                include_packages: filter.include_packages,
+                include_project_settings: filter.include_project_settings,
+                reference_extensions: filter.ref_ext.clone(),
+                follow_symlinks: filter.follow_symlinks,
+                try_encodings: filter.try_encodings,
+                skip_folders,
+                rewrite_meta_refs,
+                meta_only,
+                refs_only,
+            };
+            let flow = SyncFlow { direction, interactive, yes, skip_unreferenced, plan, plan_options, commit, max_references, only_guids: only_guid.into_iter().collect(), since, cache, resume };
+            sync_projects(main, subordinate, scan_options, options, flow, report)
+        }
+        Commands::Verify { main, subordinate, report, filter } => {
             validate_paths(&main, &subordinate)?;
-            generate_operations_report(main, subordinate, output)?;
+            let scan_options = ScanOptions { filter: filter.to_filter()?, include_packages: filter.include_packages, include_project_settings: filter.include_project_settings, reference_extensions: filter.ref_ext.clone(), follow_symlinks: filter.follow_symlinks, try_encodings: filter.try_encodings, ..Default::default() };
+            verify_sync(main, subordinate, report, scan_options)
+        }
+        Commands::Apply { subordinate, mapping, dry_run, report, allow_collisions, yes } => {
+            if !subordinate.exists() {
+                anyhow::bail!("Subordinate project path does not exist: {}", subordinate.display());
+            }
+            let options = SyncOptions {
+                dry_run,
+                allow_collisions,
+                top: DEFAULT_TOP,
+                ..Default::default()
+            };
+            apply_mapping(subordinate, mapping, options, report, yes)
+        }
+        Commands::Doctor { project, filter } => {
+            if !project.exists() {
+                anyhow::bail!("Project path does not exist: {}", project.display());
+            }
+            if !project.join("Assets").exists() && !project.ends_with("Assets") {
+                anyhow::bail!("Project does not contain an Assets folder");
+            }
+            let scan_options = ScanOptions { filter: filter.to_filter()?, include_packages: filter.include_packages, include_project_settings: filter.include_project_settings, reference_extensions: filter.ref_ext.clone(), follow_symlinks: filter.follow_symlinks, try_encodings: filter.try_encodings, ..Default::default() };
+            check_duplicates(project, scan_options)
+        }
+        Commands::Stats { project, format, filter } => {
+            if !project.exists() {
+                anyhow::bail!("Project path does not exist: {}", project.display());
+            }
+            if !project.join("Assets").exists() && !project.ends_with("Assets") {
+                anyhow::bail!("Project does not contain an Assets folder");
+            }
+            let scan_options = ScanOptions { filter: filter.to_filter()?, include_packages: filter.include_packages, include_project_settings: filter.include_project_settings, reference_extensions: filter.ref_ext.clone(), follow_symlinks: filter.follow_symlinks, try_encodings: filter.try_encodings, ..Default::default() };
+            project_stats(project, scan_options, format)
         }
-        Commands::Sync { main, subordinate, dry_run, verbose, report } => {
+        Commands::Undo { report, subordinate, dry_run, yes } => {
+            if !subordinate.exists() {
+                anyhow::bail!("Subordinate project path does not exist: {}", subordinate.display());
+            }
+            let options = SyncOptions { dry_run, top: DEFAULT_TOP, ..Default::default() };
+            undo_sync(report, subordinate, options, yes)
+        }
+        Commands::Watch { main, subordinate, allow_collisions, yes, filter } => {
             validate_paths(&main, &subordinate)?;
-            sync_projects(main, subordinate, dry_run, verbose, report)?;
+            let options = SyncOptions { allow_collisions, top: DEFAULT_TOP, ..Default::default() };
+            let scan_options = ScanOptions { filter: filter.to_filter()?, include_packages: filter.include_packages, include_project_settings: filter.include_project_settings, reference_extensions: filter.ref_ext.clone(), follow_symlinks: filter.follow_symlinks, try_encodings: filter.try_encodings, ..Default::default() };
+            watch_projects(main, subordinate, scan_options, options, yes)
+        }
+        Commands::Init { main, subordinate, force } => {
+            let path = Path::new(config::CONFIG_FILE_NAME);
+            if path.exists() && !force {
+                anyhow::bail!("{} already exists; pass --force to overwrite it", path.display());
+            }
+            fs::write(path, Config::template(main.as_deref(), subordinate.as_deref()))
+                .with_context(|| format!("Failed to write {}", path.display()))?;
+            status!("{}", format!("Wrote {}", path.display()).bright_green());
+            Ok(ExitCode::SUCCESS)
         }
     }
-    
-    Ok(())
 }
 
-fn validate_paths(main: &PathBuf, subordinate: &PathBuf) -> Result<()> {
+fn load_path_map(path: Option<&Path>) -> Result<std::collections::HashMap<PathBuf, PathBuf>> {
+    match path {
+        Some(path) => Ok(mapping_file::load_path_map(path)?),
+        None => Ok(Default::default()),
+    }
+}
+
+/// Parse a `--only-guid` value, normalized to lowercase
+fn parse_guid(value: &str) -> std::result::Result<String, String> {
+    if mapping_file::is_valid_guid(value) {
+        Ok(value.to_ascii_lowercase())
+    } else {
+        Err("expected 32 hex characters".to_string())
+    }
+}
+
+/// Parse a `--guid-prefix` value: 1 to 32 hex characters, normalized to lowercase
+fn parse_guid_prefix(value: &str) -> std::result::Result<String, String> {
+    if value.is_empty() || value.len() > 32 || !value.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err("expected 1 to 32 hex characters".to_string());
+    }
+    Ok(value.to_ascii_lowercase())
+}
+
+fn validate_paths(main: &Path, subordinate: &Path) -> Result<()> {
+    for project in [main, subordinate] {
+        if project_archive::is_archive(project) {
+            anyhow::bail!(
+                "{} is an archive; only scan can read zipped projects, extract it first to use this command",
+                project.display()
+            );
+        }
+    }
     if !main.exists() {
         anyhow::bail!("Main project path does not exist: {}", main.display());
     }
@@ -109,142 +889,1373 @@ fn validate_paths(main: &PathBuf, subordinate: &PathBuf) -> Result<()> {
         anyhow::bail!("Subordinate project does not contain an Assets folder");
     }
     
+    // Compare the Assets folders, so `Project` and `Project/Assets` count as the same project
+    let main_assets = assets_path(main.to_path_buf()).canonicalize()?;
+    let sub_assets = assets_path(subordinate.to_path_buf()).canonicalize()?;
+    
+    // An Assets folder that is a symlink into the other project would have the sync rewrite
+    // that project's own files; folders that are really nested only get the warning below
+    let main_root = project_root(main)?;
+    let sub_root = project_root(subordinate)?;
+    for (name, assets, root, other_name, other_root) in [
+        ("Subordinate", &sub_assets, &sub_root, "main", &main_root),
+        ("Main", &main_assets, &main_root, "subordinate", &sub_root),
+    ] {
+        if assets.starts_with(other_root) && !root.starts_with(other_root) {
+            anyhow::bail!(
+                "{} Assets folder resolves to {}, inside the {} project (is it a symlink?); syncing would modify that project's own files",
+                name,
+                assets.display(),
+                other_name
+            );
+        }
+    }
+    
+    if main_assets == sub_assets {
+        anyhow::bail!(
+            "Main and subordinate are the same project ({}); pass two different projects",
+            main_assets.display()
+        );
+    }
+    if main_assets.starts_with(&sub_assets) || sub_assets.starts_with(&main_assets) {
+        tracing::warn!(
+            "One project is nested inside the other ({} and {}), results may be misleading",
+            main_assets.display(),
+            sub_assets.display()
+        );
+    }
+    
     Ok(())
 }
 
-fn generate_operations_report(main: PathBuf, subordinate: PathBuf, output: PathBuf) -> Result<()> {
-    println!("{}", "Unity GUID Sync Operations Reporter".bright_white().bold());
-    println!("{}", "====================================".bright_white());
-    println!("Main project: {}", main.display().to_string().green());
-    println!("Subordinate project: {}", subordinate.display().to_string().yellow());
-    println!("Output report: {}", output.display().to_string().bright_cyan());
-    println!();
-    
-    // Adjust paths to Assets folder if needed
-    let main_path = if main.ends_with("Assets") {
-        main
+/// Extract a zipped project for scanning. Only meta and reference files are needed, unless
+/// assets are paired by content.
+fn extract_if_archive(project: &Path, scan_options: &ScanOptions) -> Result<Option<ProjectArchive>> {
+    if !project_archive::is_archive(project) {
+        return Ok(None);
+    }
+    let archive = ProjectArchive::extract(project, |path| {
+        if scan_options.match_by == MatchMode::Content {
+            return true;
+        }
+        let Some(ext) = path.extension().and_then(|ext| ext.to_str()) else {
+            return false;
+        };
+        ext.eq_ignore_ascii_case("meta")
+            || if scan_options.reference_extensions.is_empty() {
+                DEFAULT_REFERENCE_EXTENSIONS.iter().any(|known| known.eq_ignore_ascii_case(ext))
+            } else {
+                scan_options.reference_extensions.iter().any(|known| known.trim_start_matches('.').eq_ignore_ascii_case(ext))
+            }
+    })?;
+    Ok(Some(archive))
+}
+
+/// The real folder holding a project's Assets folder, without resolving Assets itself
+fn project_root(project: &Path) -> Result<PathBuf> {
+    let assets = assets_path(project.to_path_buf());
+    let root = assets.parent().filter(|parent| !parent.as_os_str().is_empty()).unwrap_or(Path::new("."));
+    root.canonicalize()
+        .with_context(|| format!("Failed to resolve project folder: {}", root.display()))
+}
+
+/// A new file in `dir` named after the current time, e.g. `guid-sync-report-20250102-030405.json`
+/// (UTC), so reports written regularly keep a history. Creates `dir` if needed.
+fn timestamped_report_path(dir: &Path, format: ReportFormat) -> Result<PathBuf> {
+    fs::create_dir_all(dir).with_context(|| format!("Failed to create report folder: {}", dir.display()))?;
+    let stem = format!("guid-sync-report-{}", utc_timestamp(std::time::SystemTime::now()));
+    let mut path = dir.join(format!("{}.{}", stem, format.extension()));
+    // Two reports within the same second get a counter instead of overwriting each other
+    let mut counter = 1;
+    while path.exists() {
+        counter += 1;
+        path = dir.join(format!("{}-{}.{}", stem, counter, format.extension()));
+    }
+    Ok(path)
+}
+
+/// `YYYYMMDD-HHMMSS` in UTC
+fn utc_timestamp(time: std::time::SystemTime) -> String {
+    let secs = time.duration_since(std::time::UNIX_EPOCH).map_or(0, |elapsed| elapsed.as_secs());
+    let (days, time_of_day) = (secs / 86_400, secs % 86_400);
+    // Civil date from days since 1970-01-01, see http://howardhinnant.github.io/date_algorithms.html
+    let z = days as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    format!(
+        "{:04}{:02}{:02}-{:02}{:02}{:02}",
+        year,
+        month,
+        day,
+        time_of_day / 3600,
+        time_of_day % 3600 / 60,
+        time_of_day % 60
+    )
+}
+
+/// The project of a subordinate file given to `sync`: the nearest enclosing Assets folder,
+/// whose meta files say which GUIDs the file's references point at
+fn file_project(file: &Path) -> Result<PathBuf> {
+    match file.ancestors().skip(1).find(|dir| dir.file_name() == Some("Assets".as_ref())) {
+        Some(assets) => Ok(assets.to_path_buf()),
+        None => anyhow::bail!(
+            "{} is not inside a Unity project's Assets folder; its GUIDs are looked up in the meta files of that project",
+            file.display()
+        ),
+    }
+}
+
+/// Adjust a project path to its Assets folder if needed
+fn assets_path(project: PathBuf) -> PathBuf {
+    if project.ends_with("Assets") {
+        project
     } else {
-        main.join("Assets")
-    };
+        project.join("Assets")
+    }
+}
+
+/// Where and how `report` writes the operations report
+struct ReportOutput {
+    /// Output file, `-` for stdout
+    path: PathBuf,
+    format: ReportFormat,
+    with_context: bool,
+    /// Number of operations listed on the console
+    top: usize,
+    /// Browse the report in the terminal UI once it is written
+    tui: bool,
+    /// Count references in the main project as well
+    scan_main_refs: bool,
+    /// Look for references to GUIDs no meta file has
+    report_dangling: bool,
+}
+
+fn generate_operations_report(main: PathBuf, subordinate: PathBuf, scan_options: ScanOptions, report_output: ReportOutput) -> Result<ExitCode> {
+    let ReportOutput { path: output, format, with_context, top, tui, scan_main_refs, report_dangling } = report_output;
+    // With `-` stdout carries only the report, warnings still go to stderr
+    if output == Path::new("-") {
+        if tui {
+            anyhow::bail!("--tui cannot be used with `--output -`, write the report to a file");
+        }
+        let mut syncer = GuidSyncer::new(assets_path(main), assets_path(subordinate))
+            .with_scan_options(scan_options)
+            .with_observer(ConsoleObserver::new(true));
+        syncer.scan_projects()?;
+        let mut report = syncer.generate_sync_operations_report(with_context)?;
+        if scan_main_refs {
+            syncer.add_main_reference_counts(&mut report);
+        }
+        if report_dangling {
+            syncer.add_dangling_references(&mut report);
+        }
+        println!("{}", report.render(format)?.trim_end());
+        return Ok(ExitCode::SUCCESS);
+    }
     
-    let sub_path = if subordinate.ends_with("Assets") {
-        subordinate
-    } else {
-        subordinate.join("Assets")
-    };
+    status!("{}", "Unity GUID Sync Operations Reporter".bright_white().bold());
+    status!("{}", "====================================".bright_white());
+    status!("Main project: {}", main.display().to_string().green());
+    status!("Subordinate project: {}", subordinate.display().to_string().yellow());
+    status!("Output report: {}", output.display().to_string().bright_cyan());
+    status!();
     
-    let mut syncer = GuidSyncer::new(main_path, sub_path);
+    let main_path = assets_path(main);
+    let sub_path = assets_path(subordinate);
+    
+    let mut syncer = GuidSyncer::new(main_path, sub_path)
+        .with_scan_options(scan_options)
+        .with_observer(ConsoleObserver::new(false));
     syncer.scan_projects()?;
+    print_scan_findings(&syncer);
     
-    let report = syncer.generate_sync_operations_report()?;
+    let mut report = syncer.generate_sync_operations_report(with_context)?;
+    if scan_main_refs {
+        syncer.add_main_reference_counts(&mut report);
+    }
+    if report_dangling {
+        syncer.add_dangling_references(&mut report);
+    }
     
     // Save report to file
-    let json = serde_json::to_string_pretty(&report)?;
-    fs::write(&output, json)?;
+    fs::write(&output, report.render(format)?)?;
     
     // Print summary
-    println!("\n{}", "Report Summary:".bright_white().bold());
-    println!("  Total GUID to change: {}", report.summary.total_guid_differences);
-    println!("  Meta files to update: {}", report.summary.total_meta_files_to_update);
-    println!("  Files with references: {}", report.summary.total_files_with_references);
-    println!("  Total reference updates: {}", report.summary.total_reference_updates);
-    
-    for (i, op) in report.operations.iter().take(10).enumerate() {
-        println!("  {}. {} ({} references)", 
+    status!("\n{}", "Report Summary:".bright_white().bold());
+    status!("  Total GUID to change: {}", report.summary.total_guid_differences);
+    status!("  Meta files to update: {}", report.summary.total_meta_files_to_update);
+    status!("  Files with references: {}", report.summary.total_files_with_references);
+    status!("  Total reference updates: {}", report.summary.total_reference_updates);
+    if !report.skipped_files.is_empty() {
+        status!("  {}", format!("Skipped unreadable files: {}", report.skipped_files.len()).bright_yellow());
+    }
+    
+    for (i, op) in report.operations.iter().take(top_limit(top)).enumerate() {
+        let main_refs = op.main_reference_count.map(|count| format!(", {} in main", count)).unwrap_or_default();
+        let importer = if op.asset_importer.is_empty() { String::new() } else { format!(" [{}]", op.asset_importer) };
+        status!("  {}. {}{} ({} references{}, {} risk)", 
             i + 1,
             op.asset_name.bright_yellow(),
-            op.total_references
+            importer.dimmed(),
+            op.total_references,
+            main_refs,
+            op.risk.as_str()
         );
-        println!("     {} -> {}", 
-            op.old_guid[..8].to_string().red(),
-            op.new_guid[..8].to_string().green()
+        status!("     {} -> {}", 
+            theme::old(&op.old_guid[..8]),
+            theme::new(&op.new_guid[..8])
         );
     }
     
-    println!("\n{}", format!("Full report saved to: {}", output.display()).bright_green());
-    
-    Ok(())
-}
-
-fn scan_projects(main: PathBuf, subordinate: PathBuf) -> Result<()> {
-    println!("{}", "Unity GUID Scanner".bright_white().bold());
-    println!("{}", "===================".bright_white());
-    println!("Main project: {}", main.display().to_string().green());
-    println!("Subordinate project: {}", subordinate.display().to_string().yellow());
-    println!();
+    if report_dangling {
+        print_dangling(&report.dangling_references, top);
+    }
     
-    // Adjust paths to Assets folder if needed
-    let main_path = if main.ends_with("Assets") {
-        main
-    } else {
-        main.join("Assets")
-    };
+    if tui {
+        review_tui::run(&report)?;
+    }
     
-    let sub_path = if subordinate.ends_with("Assets") {
-        subordinate
-    } else {
-        subordinate.join("Assets")
-    };
+    status!("\n{}", format!("Full report saved to: {}", output.display()).bright_green());
     
-    let mut syncer = GuidSyncer::new(main_path, sub_path);
-    syncer.scan_projects()?;
-    syncer.print_summary();
-    
-    Ok(())
+    Ok(ExitCode::SUCCESS)
+}
+
+/// List the referenced GUIDs that no meta file has, with the files referencing them
+fn print_dangling(dangling: &[DanglingReference], top: usize) {
+    if dangling.is_empty() {
+        status!("\n{}", "No dangling references found".bright_green());
+        return;
+    }
+    status!("\n{}", format!("Dangling references ({} GUIDs without a meta file):", dangling.len()).bright_red().bold());
+    for reference in dangling.iter().take(top_limit(top)) {
+        status!("  {}", reference.guid.bright_yellow());
+        for (path, count) in &reference.files {
+            status!("    {} - {} references", path.display(), count);
+        }
+    }
+    if dangling.len() > top_limit(top) {
+        status!("  ... and {} more, see the report", dangling.len() - top_limit(top));
+    }
+}
+
+/// What `scan` prints once the projects are compared
+struct ScanOutput {
+    format: OutputFormat,
+    /// Show the reference count of every difference in the summary
+    with_counts: bool,
+    summary_only: bool,
+    /// Browse the operations in the terminal UI instead
+    tui: bool,
 }
 
-fn sync_projects(main: PathBuf, subordinate: PathBuf, dry_run: bool, verbose: bool, report_path: Option<PathBuf>) -> Result<()> {
-    println!("{}", "Unity GUID Synchronizer".bright_white().bold());
-    println!("{}", "========================".bright_white());
-    println!("Main project: {}", main.display().to_string().green());
-    println!("Subordinate project: {}", subordinate.display().to_string().yellow());
-    if dry_run {
-        println!("{}", "Mode: DRY RUN (no changes will be made)".bright_cyan());
+fn scan_projects(main: PathBuf, subordinate: PathBuf, scan_options: ScanOptions, cache: Option<PathBuf>, exit_zero: bool, output: ScanOutput) -> Result<ExitCode> {
+    let ScanOutput { format, with_counts, summary_only, tui } = output;
+    // Zipped projects are extracted for the scan only; the temp dirs go away on return
+    let main_archive = extract_if_archive(&main, &scan_options)?;
+    let sub_archive = extract_if_archive(&subordinate, &scan_options)?;
+    let main_project = main_archive.as_ref().map_or(main.clone(), |archive| archive.root().to_path_buf());
+    let sub_project = sub_archive.as_ref().map_or(subordinate.clone(), |archive| archive.root().to_path_buf());
+    validate_paths(&main_project, &sub_project)?;
+
+    if format == OutputFormat::Json || summary_only {
+        colored::control::set_override(false);
     } else {
-        println!("{}", "Mode: LIVE (files will be modified)".bright_red().bold());
+        status!("{}", "Unity GUID Scanner".bright_white().bold());
+        status!("{}", "===================".bright_white());
+        status!("Main project: {}", main.display().to_string().green());
+        status!("Subordinate project: {}", subordinate.display().to_string().yellow());
+        status!();
     }
-    if verbose {
-        println!("{}", "Verbose: ON".bright_magenta());
+    
+    let main_path = assets_path(main_project);
+    let sub_path = assets_path(sub_project);
+    
+    let mut syncer = GuidSyncer::new(main_path, sub_path)
+        .with_scan_options(scan_options)
+        .with_observer(ConsoleObserver::new(format == OutputFormat::Json || summary_only));
+    if let Some(cache) = &cache {
+        syncer.load_cache(cache)?;
+    }
+    syncer.scan_projects()?;
+    if let Some(cache) = &cache {
+        syncer.save_cache(cache)?;
+    }
+    match format {
+        OutputFormat::Text if tui => review_tui::run(&syncer.generate_sync_operations_report(false)?)?,
+        OutputFormat::Text if summary_only => {
+            let summary = syncer.generate_sync_operations_report(false)?.summary;
+            println!(
+                "guid-sync: {} differences, {} meta files, {} references",
+                summary.total_guid_differences, summary.total_meta_files_to_update, summary.total_reference_updates
+            );
+        }
+        OutputFormat::Text => {
+            print_scan_findings(&syncer);
+            if with_counts && quiet() {
+                tracing::warn!("--with-counts has no effect with --quiet, the reference counts are not printed");
+            }
+            // Counting takes a walk over every reference file, so it is only done on request
+            let counts = (with_counts && !quiet()).then(|| syncer.reference_counts());
+            if !quiet() {
+                print_mapping_summary(&syncer, counts.as_ref());
+            }
+            if let Some(counts) = &counts {
+                print_unreferenced(&syncer.unreferenced_mappings(counts));
+            }
+        }
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&syncer.scan_result())?),
     }
-    println!();
     
-    // Adjust paths to Assets folder if needed
-    let main_path = if main.ends_with("Assets") {
-        main
+    if syncer.get_difference_count() > 0 && !exit_zero {
+        return Ok(ExitCode::from(EXIT_DIFFERENCES_FOUND));
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Print every GUID difference and collision the last scan found
+fn print_scan_findings(syncer: &GuidSyncer) {
+    let differences = syncer.diff();
+    for difference in &differences {
+        let found = match &difference.paired_with {
+            Some(main_path) => format!(
+                "GUID difference found for {} (paired with {})",
+                difference.relative_path.display(),
+                main_path.display()
+            ),
+            None => format!("GUID difference found for {}", difference.relative_path.display()),
+        };
+        status!("{}: {} -> {}", found.yellow(), theme::old(&difference.sub_guid), theme::new(&difference.main_guid));
+    }
+    status!("{}", format!("Found {} GUID differences", differences.len()).bright_yellow());
+    
+    for collision in syncer.get_collisions() {
+        status!(
+            "{}: {} would take GUID {} already used by {}",
+            "GUID collision".bright_red().bold(),
+            collision.asset_path.display(),
+            collision.guid.bright_yellow(),
+            collision.existing_path.display()
+        );
+    }
+    
+    for ambiguous in syncer.ambiguous_guids() {
+        status!(
+            "{}: subordinate GUID {} maps to different main GUIDs",
+            "Ambiguous GUID".bright_red().bold(),
+            ambiguous.sub_guid.bright_yellow()
+        );
+        for (meta_path, main_guid) in &ambiguous.candidates {
+            status!("  {} -> {}", meta_path.display(), theme::new(main_guid));
+        }
+    }
+    
+    for meta_path in syncer.orphaned_metas() {
+        status!("{}: {}", "Orphaned meta (asset file missing)".bright_yellow(), meta_path.display());
+    }
+    
+    let (main_only, sub_only) = (syncer.main_only().len(), syncer.sub_only().len());
+    if main_only > 0 || sub_only > 0 {
+        status!(
+            "{}",
+            format!("Unpaired assets: {} only in main, {} only in subordinate", main_only, sub_only).bright_yellow()
+        );
+    }
+}
+
+/// List differences whose old GUID is never referenced, so syncing them is only cosmetic
+fn print_unreferenced(unreferenced: &[PathBuf]) {
+    if unreferenced.is_empty() {
+        return;
+    }
+    status!(
+        "\n{}",
+        format!("Unreferenced ({}): nothing references the old GUID, only the meta file changes", unreferenced.len())
+            .bright_white()
+            .underline()
+    );
+    for rel_path in unreferenced {
+        status!("  {}", rel_path.display().to_string().dimmed());
+    }
+}
+
+fn report_skipped_unreferenced(count: usize) {
+    if count > 0 {
+        status!("{}", format!("Skipping {} unreferenced assets (--skip-unreferenced)", count).bright_yellow());
+    }
+}
+
+/// Sync settings that steer the command itself rather than the syncer
+struct SyncFlow {
+    direction: SyncDirection,
+    interactive: bool,
+    /// Skip the confirmation before a live sync
+    yes: bool,
+    skip_unreferenced: bool,
+    /// Where a dry run writes its planned operations
+    plan: Option<PathBuf>,
+    /// Filters and options stored in the plan, so `apply` replays them
+    plan_options: PlanOptions,
+    /// Limit on the references a live sync may rewrite
+    max_references: Option<usize>,
+    /// Commit the changed files in each rewritten project
+    commit: bool,
+    /// Lowercase GUIDs to restrict the sync to, empty for all differences
+    only_guids: HashSet<String>,
+    /// Git ref in the main project; only assets changed since then are synced
+    since: Option<String>,
+    cache: Option<PathBuf>,
+    /// Checkpoint of an interrupted sync to continue
+    resume: Option<PathBuf>,
+}
+
+fn sync_projects(main: PathBuf, subordinates: Vec<PathBuf>, scan_options: ScanOptions, options: SyncOptions, flow: SyncFlow, report_path: Option<PathBuf>) -> Result<ExitCode> {
+    let SyncFlow { direction, interactive, yes, skip_unreferenced, plan, plan_options, commit, max_references, only_guids, since, cache, resume } = flow;
+    status!("{}", "Unity GUID Synchronizer".bright_white().bold());
+    status!("{}", "========================".bright_white());
+    status!("Main project: {}", main.display().to_string().green());
+    for subordinate in &subordinates {
+        status!("Subordinate project: {}", subordinate.display().to_string().yellow());
+    }
+    if let Some(file) = &options.only_file {
+        status!("Only rewriting references in: {}", file.display().to_string().yellow());
+    }
+    if options.dry_run {
+        status!("{}", "Mode: DRY RUN (no changes will be made)".bright_cyan());
     } else {
-        main.join("Assets")
+        status!("{}", "Mode: LIVE (files will be modified)".bright_red().bold());
+    }
+    if direction != SyncDirection::MainWins {
+        let name = direction.to_possible_value().map(|v| v.get_name().to_string()).unwrap_or_default();
+        status!("Direction: {}", name.bright_magenta());
+    }
+    if let Some(backup_dir) = &options.backup_dir {
+        status!("Backup directory: {}", backup_dir.display().to_string().bright_cyan());
+    }
+    status!();
+    
+    // When the main project may be rewritten too, every project gets its own backup folder
+    let backup_folders = if direction == SyncDirection::MainWins { subordinates.len() } else { 2 };
+    let main_path = assets_path(main.clone());
+    let observer = ConsoleObserver::new(false);
+    let mut syncers: Vec<(PathBuf, GuidSyncer, SyncOptions)> = Vec::new();
+    for subordinate in subordinates.iter() {
+        let syncer = GuidSyncer::new(main_path.clone(), assets_path(subordinate.clone()))
+            .with_scan_options(scan_options.clone())
+            .with_observer(observer.clone());
+        let sub_options = SyncOptions {
+            backup_dir: project_subdir(options.backup_dir.as_deref(), subordinate, backup_folders)?,
+            shadow_dir: project_subdir(options.shadow_dir.as_deref(), subordinate, backup_folders)?,
+            ..options.clone()
+        };
+        if let Some(backup_dir) = &sub_options.backup_dir {
+            if syncers.iter().any(|(_, _, other)| other.backup_dir.as_ref() == Some(backup_dir)) {
+                anyhow::bail!("Subordinate projects share the backup folder name {}", backup_dir.display());
+            }
+        }
+        if let Some(shadow_dir) = &sub_options.shadow_dir {
+            if syncers.iter().any(|(_, _, other)| other.shadow_dir.as_ref() == Some(shadow_dir)) {
+                anyhow::bail!("Subordinate projects share the shadow folder name {}", shadow_dir.display());
+            }
+        }
+        syncers.push((subordinate.clone(), syncer, sub_options));
+    }
+    
+    // The main project is scanned once and shared between all subordinates
+    if let Some(cache) = &cache {
+        syncers[0].1.load_cache(cache)?;
+    }
+    let main_metas = syncers[0].1.scan_main_project()?;
+    if let Some(cache) = &cache {
+        syncers[0].1.save_cache(cache)?;
+    }
+    let since_guids = match &since {
+        Some(since) => Some(changed_main_guids(&main_path, since)?),
+        None => None,
+    };
+    // Assets the subordinate wins are synced by a second syncer that rewrites the main project
+    let mut reversed = None;
+    let mut matched_guids = HashSet::new();
+    for (subordinate, syncer, sub_options) in syncers.iter_mut() {
+        if subordinates.len() > 1 {
+            status!("\n{}", format!("Subordinate: {}", subordinate.display()).bright_white().bold());
+        }
+        syncer.scan_projects_with_main(&main_metas)?;
+        print_scan_findings(syncer);
+        if let (Some(since), Some(guids)) = (&since, &since_guids) {
+            let found = syncer.get_difference_count();
+            syncer.retain_guids(guids)?;
+            status!("{} of {} GUID differences changed in main since {}", syncer.get_difference_count(), found, since);
+        }
+        if !only_guids.is_empty() {
+            matched_guids.extend(syncer.retain_guids(&only_guids)?);
+        }
+        
+        if direction != SyncDirection::MainWins {
+            if let Some(main_syncer) = syncer.split_by_direction(direction)? {
+                reversed = Some(main_syncer);
+            }
+        }
+        if skip_unreferenced {
+            report_skipped_unreferenced(syncer.skip_unreferenced()?);
+        }
+        if interactive && syncer.get_difference_count() > 0 {
+            choose_mappings(syncer)?;
+        }
+        
+        if verbose() {
+            print_mapping_summary(syncer, None);
+        } else {
+            // Just show count for non-verbose
+            status!("Found {} GUID differences to resolve", syncer.get_difference_count());
+        }
+        
+        syncer.check_collisions(sub_options)?;
+        if !syncer.get_collisions().is_empty() {
+            status!("{}", "WARNING: Proceeding despite GUID collisions (--allow-collisions)".bright_red());
+        }
+        // Fail before the confirmation prompt rather than once the sync starts
+        syncer.check_ambiguous(sub_options)?;
+    }
+    
+    let mut unmatched: Vec<&String> = only_guids.difference(&matched_guids).collect();
+    if !unmatched.is_empty() {
+        unmatched.sort();
+        anyhow::bail!(
+            "--only-guid {} is not among the GUID differences found",
+            unmatched.iter().map(|guid| guid.as_str()).collect::<Vec<_>>().join(", ")
+        );
+    }
+    
+    if let Some(mut main_syncer) = reversed {
+        status!("\n{}", format!("Main project: {}", main.display()).bright_white().bold());
+        if skip_unreferenced {
+            report_skipped_unreferenced(main_syncer.skip_unreferenced()?);
+        }
+        status!("Found {} GUIDs kept from the subordinate project", main_syncer.get_difference_count());
+        let main_options = SyncOptions {
+            backup_dir: project_subdir(options.backup_dir.as_deref(), &main, backup_folders)?,
+            shadow_dir: project_subdir(options.shadow_dir.as_deref(), &main, backup_folders)?,
+            ..options.clone()
+        };
+        main_syncer.check_collisions(&main_options)?;
+        if let Some(backup_dir) = &main_options.backup_dir {
+            if syncers.iter().any(|(_, _, other)| other.backup_dir.as_ref() == Some(backup_dir)) {
+                anyhow::bail!("Main and subordinate projects share the backup folder name {}", backup_dir.display());
+            }
+        }
+        syncers.push((main.clone(), main_syncer, main_options));
+    }
+    
+    if let (Some(checkpoint), [(_, syncer, _)]) = (&resume, syncers.as_mut_slice()) {
+        let completed = syncer.resume_from_checkpoint(checkpoint)?;
+        status!(
+            "{}",
+            format!("Resuming from {}: {} file updates already done", checkpoint.display(), completed).bright_cyan()
+        );
+    }
+    
+    let total_differences: usize = syncers.iter().map(|(_, syncer, _)| syncer.get_difference_count()).sum();
+    if let (Some(limit), false) = (max_references, options.dry_run) {
+        let mut planned = 0;
+        for (_, syncer, _) in &syncers {
+            planned += syncer.generate_sync_operations_report(false)?.summary.total_reference_updates;
+        }
+        if planned > limit {
+            anyhow::bail!(
+                "Sync would rewrite {} references, more than --max-references {}; check the projects or raise the limit",
+                planned,
+                limit
+            );
+        }
+    }
+    if commit {
+        for (project, _, _) in &syncers {
+            git_commit::ensure_clean(project)?;
+        }
+    }
+    if !options.dry_run && total_differences > 0 {
+        status!();
+        if syncers.len() > subordinates.len() {
+            status!("{}", "WARNING: This will modify files in the main and subordinate projects!".bright_red().bold());
+        } else {
+            status!("{}", "WARNING: This will modify files in the subordinate project!".bright_red().bold());
+        }
+        confirm(yes)?;
+    }
+    
+    if let (Some(plan_path), [(_, syncer, sub_options)]) = (&plan, syncers.as_slice()) {
+        let mut planned = syncer.generate_plan(sub_options)?;
+        planned.options = Some(plan_options);
+        fs::write(plan_path, planned.render(ReportFormat::Json)?)
+            .with_context(|| format!("Failed to write plan: {}", plan_path.display()))?;
+        status!("{}", format!("Plan exported to: {}", plan_path.display()).bright_cyan());
+    }
+    
+    if let [(_, syncer, sub_options)] = syncers.as_slice() {
+        let sync_report = syncer.sync_guids(sub_options)?;
+        if commit {
+            commit_sync(&syncers[0].0, &sync_report)?;
+        }
+        
+        if let Some(report_path) = report_path {
+            sync_report.export_to_file(&report_path)?;
+            status!("\n{}", format!("Report exported to: {}", report_path.display()).bright_cyan());
+        }
+        return Ok(failed_writes_exit_code(sync_report.failed_files().len()));
+    }
+    
+    let mut combined = CombinedSyncReport::default();
+    for (subordinate, syncer, sub_options) in &syncers {
+        status!("\n{}", format!("Syncing {}", subordinate.display()).bright_white().bold());
+        let sync_report = syncer.sync_guids(sub_options)?;
+        if commit {
+            commit_sync(subordinate, &sync_report)?;
+        }
+        combined.add(subordinate.clone(), sync_report);
+    }
+    if !quiet() {
+        print_combined_summary(&combined);
+    }
+    
+    if let Some(report_path) = report_path {
+        combined.export_to_file(&report_path)?;
+        status!("\n{}", format!("Report exported to: {}", report_path.display()).bright_cyan());
+    }
+    
+    let failed = combined.subordinates.values().map(|report| report.failed_files().len()).sum();
+    Ok(failed_writes_exit_code(failed))
+}
+
+/// Exit code of a finished sync: failure if some files could not be written, even though
+/// the rest of the sync went through
+fn failed_writes_exit_code(failed: usize) -> ExitCode {
+    if failed == 0 {
+        return ExitCode::SUCCESS;
+    }
+    eprintln!(
+        "{}",
+        format!("{} files could not be written; make them writable and run the sync again", failed).bright_red().bold()
+    );
+    ExitCode::FAILURE
+}
+
+/// GUIDs of the main project's assets whose meta file or asset changed since the git ref `since`
+fn changed_main_guids(main: &Path, since: &str) -> Result<HashSet<String>> {
+    let changed = git_commit::changed_since(main, since)?;
+    let mut guids = HashSet::new();
+    for path in &changed {
+        let meta = if path.extension().is_some_and(|ext| ext == "meta") {
+            path.clone()
+        } else {
+            let mut meta = path.clone().into_os_string();
+            meta.push(".meta");
+            PathBuf::from(meta)
+        };
+        // Deleted assets have nothing left to sync
+        if meta.is_file() {
+            guids.insert(MetaFile::get_guid_from_file(&meta)?.to_ascii_lowercase());
+        }
+    }
+    status!("{} files changed in main since {}", changed.len(), since);
+    Ok(guids)
+}
+
+/// Commit the meta and reference files a sync changed in `project`, if it changed any
+fn commit_sync(project: &Path, report: &SyncReport) -> Result<()> {
+    let assets = assets_path(project.to_path_buf());
+    let mut files: Vec<PathBuf> = report.files_with_references().keys().map(|path| assets.join(path)).collect();
+    files.extend(report.guid_changes().iter().map(|change| assets.join(&change.meta_path)));
+    files.sort();
+    files.dedup();
+    if files.is_empty() {
+        return Ok(());
+    }
+    
+    let message = format!(
+        "guid-sync: updated {} GUIDs, {} references",
+        report.guid_changes().len(),
+        report.total_references_replaced()
+    );
+    let hash = git_commit::commit_files(project, &files, &message)?;
+    status!("{}", format!("Committed {} in {}: {}", hash, project.display(), message).bright_green());
+    Ok(())
+}
+
+/// Ask per asset (riskiest first) whether to sync it, and drop the rest from the syncer.
+/// Answers: y = sync, n = skip, a = sync this and all remaining, q = skip this and all remaining.
+fn choose_mappings(syncer: &mut GuidSyncer) -> Result<()> {
+    let operations = syncer.generate_sync_operations_report(false)?.operations;
+    let mut approved = HashSet::new();
+    let mut answer_rest = None;
+    
+    for (i, op) in operations.iter().enumerate() {
+        let approve = match answer_rest {
+            Some(approve) => approve,
+            None => loop {
+                print!(
+                    "[{}/{}] {} {} -> {} ({} references, {} risk) [y/n/a/q] ",
+                    i + 1,
+                    operations.len(),
+                    op.asset_path.display().to_string().bright_cyan(),
+                    theme::old(&op.old_guid),
+                    theme::new(&op.new_guid),
+                    op.total_references,
+                    op.risk.as_str()
+                );
+                std::io::stdout().flush()?;
+                let mut input = String::new();
+                if std::io::stdin().read_line(&mut input)? == 0 {
+                    anyhow::bail!("Input closed while choosing assets to sync");
+                }
+                match input.trim().to_ascii_lowercase().as_str() {
+                    "y" | "yes" => break true,
+                    "n" | "no" => break false,
+                    "a" | "all" => {
+                        answer_rest = Some(true);
+                        break true;
+                    }
+                    "q" | "quit" => {
+                        answer_rest = Some(false);
+                        break false;
+                    }
+                    _ => println!("Please answer y, n, a (all) or q (quit)"),
+                }
+            },
+        };
+        if approve {
+            approved.insert(op.asset_path.clone());
+        }
+    }
+    
+    status!("Syncing {} of {} assets", approved.len(), operations.len());
+    Ok(syncer.retain_mappings(&approved)?)
+}
+
+/// With several subordinates, each one gets its own backup or shadow folder named after the project
+fn project_subdir(dir: Option<&Path>, subordinate: &Path, subordinate_count: usize) -> Result<Option<PathBuf>> {
+    let Some(dir) = dir else {
+        return Ok(None);
     };
+    if subordinate_count == 1 {
+        return Ok(Some(dir.to_path_buf()));
+    }
     
-    let sub_path = if subordinate.ends_with("Assets") {
-        subordinate
+    let project_root = subordinate.canonicalize()?;
+    let project_root = if project_root.ends_with("Assets") {
+        project_root.parent().unwrap_or(&project_root).to_path_buf()
     } else {
-        subordinate.join("Assets")
+        project_root
     };
+    let name = project_root
+        .file_name()
+        .with_context(|| format!("Cannot name a folder for {}", subordinate.display()))?;
+    Ok(Some(dir.join(name)))
+}
+
+fn verify_sync(main: PathBuf, subordinate: PathBuf, report_path: Option<PathBuf>, scan_options: ScanOptions) -> Result<ExitCode> {
+    status!("{}", "Unity GUID Sync Verifier".bright_white().bold());
+    status!("{}", "=========================".bright_white());
+    status!("Main project: {}", main.display().to_string().green());
+    status!("Subordinate project: {}", subordinate.display().to_string().yellow());
+    status!();
     
-    let mut syncer = GuidSyncer::new(main_path, sub_path);
+    let previous_sync = match &report_path {
+        Some(report_path) => Some(SyncReport::load_for_project(report_path, &subordinate)?),
+        None => None,
+    };
+    
+    let mut syncer = GuidSyncer::new(assets_path(main), assets_path(subordinate))
+        .with_scan_options(scan_options)
+        .with_observer(ConsoleObserver::new(false));
     syncer.scan_projects()?;
+    print_scan_findings(&syncer);
     
-    if verbose {
-        syncer.print_summary();
-    } else {
-        // Just show count for non-verbose
-        println!("Found {} GUID differences to resolve", syncer.get_difference_count());
+    let stale = syncer.find_stale_references(previous_sync.as_ref())?;
+    if stale.is_empty() {
+        status!("{}", "No stale GUID references found!".bright_green());
+        return Ok(ExitCode::SUCCESS);
     }
     
-    if !dry_run && syncer.get_difference_count() > 0 {
-        println!();
-        println!("{}", "WARNING: This will modify files in the subordinate project!".bright_red().bold());
+    status!("\n{}", "Stale GUID references:".bright_red().bold());
+    for reference in &stale {
+        status!(
+            "  {}:{} {} (should be {})",
+            reference.file_path.display().to_string().bright_cyan(),
+            reference.line_number,
+            theme::old(&reference.guid),
+            theme::new(&reference.expected_guid)
+        );
+    }
+    status!("{}", format!("Found {} stale references", stale.len()).bright_yellow());
+    
+    Ok(ExitCode::from(EXIT_DIFFERENCES_FOUND))
+}
+
+fn check_duplicates(project: PathBuf, scan_options: ScanOptions) -> Result<ExitCode> {
+    status!("{}", "Unity GUID Doctor".bright_white().bold());
+    status!("{}", "==================".bright_white());
+    status!("Project: {}", project.display().to_string().green());
+    status!();
+    
+    let project_path = assets_path(project);
+    let syncer = GuidSyncer::new(PathBuf::new(), project_path.clone())
+        .with_scan_options(scan_options)
+        .with_observer(ConsoleObserver::new(false));
+    let duplicates = syncer.find_duplicate_guids(&project_path)?;
+    if duplicates.is_empty() {
+        status!("{}", "No duplicate GUIDs found!".bright_green());
+        return Ok(ExitCode::SUCCESS);
+    }
+    
+    status!("{}", "Duplicate GUIDs:".bright_red().bold());
+    for duplicate in &duplicates {
+        status!("  {}", duplicate.guid.red());
+        for meta_path in &duplicate.meta_paths {
+            status!("    {}", meta_path.display().to_string().bright_cyan());
+        }
+    }
+    status!("{}", format!("Found {} duplicate GUIDs", duplicates.len()).bright_yellow());
+    
+    Ok(ExitCode::from(EXIT_DIFFERENCES_FOUND))
+}
+
+fn project_stats(project: PathBuf, scan_options: ScanOptions, format: OutputFormat) -> Result<ExitCode> {
+    let project_path = assets_path(project.clone());
+    let syncer = GuidSyncer::new(PathBuf::new(), project_path.clone())
+        .with_scan_options(scan_options)
+        .with_observer(ConsoleObserver::new(format == OutputFormat::Json));
+    let stats = syncer.project_stats(&project_path)?;
+    
+    if format == OutputFormat::Json {
+        println!("{}", serde_json::to_string_pretty(&stats)?);
+        return Ok(ExitCode::SUCCESS);
+    }
+    
+    status!("{}", "Unity GUID Stats".bright_white().bold());
+    status!("{}", "=================".bright_white());
+    status!("Project: {}", project.display().to_string().green());
+    status!();
+    println!("  Meta files: {} ({} files, {} folders)", stats.meta_files, stats.file_assets, stats.folder_assets);
+    println!("  Distinct GUIDs: {}", stats.distinct_guids);
+    println!("  Reference files: {}", stats.reference_files);
+    
+    if !stats.top_referenced.is_empty() {
+        println!("\n{}", "Most referenced GUIDs:".bright_white().underline());
+        for (i, referenced) in stats.top_referenced.iter().enumerate() {
+            let asset = match &referenced.meta_path {
+                Some(meta_path) => meta_path.display().to_string().bright_cyan(),
+                None => "(not in this project)".dimmed(),
+            };
+            println!("  {}. {} {} ({} references)", i + 1, referenced.guid.bright_yellow(), asset, referenced.references);
+        }
+    }
+    
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Wait for Enter before modifying files, unless `--yes` was given or stdin is not a terminal:
+/// scripts and CI have no one to press it
+fn confirm(yes: bool) -> Result<()> {
+    if !yes && std::io::stdin().is_terminal() {
         println!("Press Enter to continue or Ctrl+C to cancel...");
         let mut input = String::new();
         std::io::stdin().read_line(&mut input)?;
     }
+    Ok(())
+}
+
+fn apply_mapping(subordinate: PathBuf, mapping_path: PathBuf, mut options: SyncOptions, report_path: Option<PathBuf>, yes: bool) -> Result<ExitCode> {
+    status!("{}", "Unity GUID Mapping Applier".bright_white().bold());
+    status!("{}", "===========================".bright_white());
+    status!("Mapping file: {}", mapping_path.display().to_string().bright_cyan());
+    status!("Subordinate project: {}", subordinate.display().to_string().yellow());
+    if options.dry_run {
+        status!("{}", "Mode: DRY RUN (no changes will be made)".bright_cyan());
+    } else {
+        status!("{}", "Mode: LIVE (files will be modified)".bright_red().bold());
+    }
+    status!();
     
-    let sync_report = syncer.sync_guids(dry_run, verbose)?;
+    let mapping = mapping_file::load_mapping(&mapping_path)?;
+    status!("Loaded {} GUID pairs", mapping.pairs.len());
+    // A plan is applied with the filters and options of the sync that wrote it
+    let scan_options = match &mapping.options {
+        Some(plan_options) => {
+            plan_options.apply_to(&mut options);
+            plan_options.scan_options()?
+        }
+        None => ScanOptions::default(),
+    };
+    
+    let syncer = GuidSyncer::new(PathBuf::new(), assets_path(subordinate))
+        .with_scan_options(scan_options)
+        .with_observer(ConsoleObserver::new(false))
+        .with_guid_pairs(&mapping.pairs)?;
+    if verbose() {
+        print_mapping_summary(&syncer, None);
+    }
+    status!(
+        "Found {} meta files to update, {} GUIDs only referenced",
+        syncer.get_difference_count(),
+        syncer.get_reference_only_count()
+    );
+    syncer.check_collisions(&options)?;
+    
+    if !options.dry_run && syncer.get_difference_count() + syncer.get_reference_only_count() > 0 {
+        status!();
+        status!("{}", "WARNING: This will modify files in the subordinate project!".bright_red().bold());
+        confirm(yes)?;
+    }
+    
+    let sync_report = syncer.sync_guids(&options)?;
     
     if let Some(report_path) = report_path {
         sync_report.export_to_file(&report_path)?;
-        println!("\n{}", format!("Report exported to: {}", report_path.display()).bright_cyan());
+        status!("\n{}", format!("Report exported to: {}", report_path.display()).bright_cyan());
     }
     
-    Ok(())
-}
\ No newline at end of file
+    Ok(failed_writes_exit_code(sync_report.failed_files().len()))
+}
+
+fn undo_sync(report_path: PathBuf, subordinate: PathBuf, options: SyncOptions, yes: bool) -> Result<ExitCode> {
+    status!("{}", "Unity GUID Sync Undo".bright_white().bold());
+    status!("{}", "=====================".bright_white());
+    status!("Report: {}", report_path.display().to_string().bright_cyan());
+    status!("Subordinate project: {}", subordinate.display().to_string().yellow());
+    if options.dry_run {
+        status!("{}", "Mode: DRY RUN (no changes will be made)".bright_cyan());
+    } else {
+        status!("{}", "Mode: LIVE (files will be modified)".bright_red().bold());
+    }
+    status!();
+    
+    let report = SyncReport::load_for_project(&report_path, &subordinate)?;
+    let syncer = GuidSyncer::from_sync_report(assets_path(subordinate), &report).with_observer(ConsoleObserver::new(false));
+    
+    let drifted = syncer.detect_drift(&report)?;
+    if !drifted.is_empty() {
+        println!("{}", "Subordinate project has changed since the sync:".bright_red().bold());
+        for (path, reason) in &drifted {
+            println!("  {} - {}", path.display().to_string().bright_yellow(), reason);
+        }
+        anyhow::bail!("Refusing to undo: {} files diverged from the report", drifted.len());
+    }
+    
+    status!("Found {} GUID changes to revert", syncer.get_difference_count());
+    
+    if !options.dry_run && syncer.get_difference_count() > 0 {
+        status!();
+        status!("{}", "WARNING: This will modify files in the subordinate project!".bright_red().bold());
+        confirm(yes)?;
+    }
+    
+    let sync_report = syncer.sync_guids(&options)?;
+    
+    Ok(failed_writes_exit_code(sync_report.failed_files().len()))
+}
+
+/// Quiet period after the last change before a burst of meta file changes is synced
+const WATCH_DEBOUNCE: Duration = Duration::from_millis(500);
+
+fn watch_projects(main: PathBuf, subordinate: PathBuf, scan_options: ScanOptions, options: SyncOptions, yes: bool) -> Result<ExitCode> {
+    status!("{}", "Unity GUID Watcher".bright_white().bold());
+    status!("{}", "===================".bright_white());
+    status!("Main project: {}", main.display().to_string().green());
+    status!("Subordinate project: {}", subordinate.display().to_string().yellow());
+    status!();
+    
+    status!("{}", "WARNING: This will keep modifying files in the subordinate project!".bright_red().bold());
+    confirm(yes)?;
+    
+    let main_path = assets_path(main);
+    let mut syncer = GuidSyncer::new(main_path.clone(), assets_path(subordinate))
+        .with_scan_options(scan_options)
+        .with_observer(Arc::new(ConsoleObserver { silent: quiet(), list_changes: true }));
+    
+    // Bring the projects in sync once, after that only changed assets are looked at
+    syncer.scan_projects()?;
+    syncer.check_collisions(&options)?;
+    syncer.sync_guids(&options)?;
+    
+    let running = Arc::new(AtomicBool::new(true));
+    let handler_flag = Arc::clone(&running);
+    ctrlc::set_handler(move || handler_flag.store(false, Ordering::SeqCst))
+        .context("Failed to install Ctrl+C handler")?;
+    
+    let (tx, rx) = mpsc::channel();
+    let mut debouncer = new_debouncer(WATCH_DEBOUNCE, tx).context("Failed to start file watcher")?;
+    debouncer
+        .watcher()
+        .watch(&main_path, RecursiveMode::Recursive)
+        .with_context(|| format!("Failed to watch {}", main_path.display()))?;
+    status!("{}", format!("Watching {} (Ctrl+C to stop)", main_path.display()).bright_blue());
+    
+    while running.load(Ordering::SeqCst) {
+        let events = match rx.recv_timeout(Duration::from_millis(200)) {
+            Ok(Ok(events)) => events,
+            Ok(Err(e)) => {
+                tracing::warn!("File watcher error: {}", e);
+                continue;
+            }
+            Err(RecvTimeoutError::Timeout) => continue,
+            Err(RecvTimeoutError::Disconnected) => break,
+        };
+        
+        let mut changed: Vec<PathBuf> = events
+            .iter()
+            .filter(|event| event.path.extension().is_some_and(|ext| ext == "meta"))
+            .filter_map(|event| event.path.strip_prefix(&main_path).ok().map(Path::to_path_buf))
+            .collect();
+        changed.sort();
+        changed.dedup();
+        if changed.is_empty() {
+            continue;
+        }
+        
+        syncer.rescan_assets(&changed)?;
+        if syncer.get_difference_count() == 0 {
+            continue;
+        }
+        if let Err(e) = syncer.check_collisions(&options) {
+            tracing::warn!("Skipping changes: {}", e);
+            continue;
+        }
+        syncer.sync_guids(&options)?;
+    }
+    
+    status!("{}", "Stopped watching".bright_blue());
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Shows the progress and outcome of scans and syncs on the terminal
+struct ConsoleObserver {
+    /// Print nothing, as with --quiet, for output that other programs read
+    silent: bool,
+    /// One line per GUID change as the syncer makes it, instead of the progress, for `watch`
+    list_changes: bool,
+}
+
+impl ConsoleObserver {
+    /// Progress and status lines, unless `silent` or --quiet
+    fn new(silent: bool) -> Arc<Self> {
+        Arc::new(Self { silent: silent || quiet(), list_changes: false })
+    }
+
+    fn shows_progress(&self) -> bool {
+        !self.silent && !self.list_changes
+    }
+}
+
+impl SyncObserver for ConsoleObserver {
+    fn on_scan_started(&self, project: &Path) {
+        if self.shows_progress() {
+            println!("{}", format!("Scanning {}...", project.display()).bright_blue());
+            show_progress(|| spinner("Reading meta files"));
+        }
+    }
+
+    fn on_file_scanned(&self, _meta_path: &Path) {
+        advance_progress();
+    }
+
+    fn on_scan_finished(&self, _project: &Path, _files: usize) {
+        clear_progress();
+    }
+
+    fn on_references_started(&self, pass: ReferencePass) {
+        if !self.shows_progress() {
+            return;
+        }
+        match pass {
+            ReferencePass::Rewrite { files } => show_progress(|| progress_bar(files, "Updating references")),
+            ReferencePass::Count => {
+                println!("{}", "Generating detailed sync operations report...".bright_blue());
+                show_progress(|| spinner("Counting references"));
+            }
+        }
+    }
+
+    fn on_reference_file_read(&self) {
+        advance_progress();
+    }
+
+    fn on_references_finished(&self) {
+        clear_progress();
+    }
+
+    fn on_meta_updated(&self, change: &GuidChange) {
+        if self.list_changes && !self.silent {
+            println!(
+                "{} {}: {} -> {}",
+                "Synced".green(),
+                change.meta_path.display(),
+                theme::old(&change.old_guid),
+                theme::new(&change.new_guid)
+            );
+        }
+    }
+
+    fn on_nothing_to_sync(&self) {
+        if self.shows_progress() {
+            println!("{}", "No GUID differences to resolve!".green());
+        }
+    }
+
+    fn on_sync_finished(&self, report: &SyncReport, options: &SyncOptions) {
+        // Kept under --quiet, which only silences progress and summaries
+        if options.dry_run && options.show_diff {
+            print_diffs(report.diffs(), options.max_diff_lines);
+        }
+        if self.silent {
+            return;
+        }
+        if self.list_changes {
+            // The reference totals after the lines of a sync that changed GUIDs
+            if !report.guid_changes().is_empty() {
+                println!("  {}", report.summary_line());
+            }
+            return;
+        }
+
+        if options.dry_run {
+            print_dry_run_summary(report, options.top);
+        }
+        if let Some(backup_dir) = &options.backup_dir {
+            if !report.backed_up_files().is_empty() {
+                println!(
+                    "{}",
+                    format!("Backed up {} files to {}", report.backed_up_files().len(), backup_dir.display()).bright_cyan()
+                );
+            }
+        }
+        if !options.dry_run && !report.skipped_files().is_empty() {
+            println!(
+                "{}",
+                format!("{} files could not be read and were skipped", report.skipped_files().len()).bright_yellow()
+            );
+        }
+        if !report.failed_files().is_empty() {
+            println!(
+                "{}",
+                format!("{} files could not be written and were left unchanged:", report.failed_files().len())
+                    .bright_red()
+                    .bold()
+            );
+            for (path, reason) in report.failed_files() {
+                println!("  {} ({})", path.display(), reason);
+            }
+        }
+        if !options.dry_run {
+            println!("Modified files: {}", report.size_line());
+        }
+        println!("{}", "GUID sync completed!".bright_green());
+    }
+}
+
+/// Print every GUID difference, with the number of references to its old GUID when `counts`
+/// (from `reference_counts`) is given
+fn print_mapping_summary(syncer: &GuidSyncer, counts: Option<&HashMap<String, usize>>) {
+    let differences = syncer.diff();
+    if differences.is_empty() {
+        return;
+    }
+
+    println!("\n{}", "GUID Mapping Summary:".bright_white().underline());
+    for mapping in differences {
+        let path = mapping.relative_path.display().to_string().bright_cyan();
+        match counts {
+            Some(counts) => {
+                let references = counts.get(&mapping.sub_guid).copied().unwrap_or(0);
+                println!("  {}: {} references", path, references);
+            }
+            None => println!("  {}", path),
+        }
+        println!("    {} {}", "Main:".green(), theme::new(&mapping.main_guid));
+        println!("    {} {}", "Sub: ".red(), theme::old(&mapping.sub_guid));
+    }
+}
+
+/// Print the summary line of every subordinate of a multi-project sync, then their total
+fn print_combined_summary(combined: &CombinedSyncReport) {
+    println!("\n{}", "Per-project summary:".bright_white().underline());
+    for (subordinate, report) in &combined.subordinates {
+        println!("  {}: {}", subordinate.display().to_string().yellow(), report.summary_line());
+    }
+    println!("  {}: {}", "Total".bright_white().bold(), combined.total_line());
+}
+
+/// Print the dry run summary, listing the `top` most referenced GUIDs (0 for all)
+fn print_dry_run_summary(report: &SyncReport, top: usize) {
+    println!("\n{}", "═══════════════════════════════════════".bright_white());
+    println!("{}", "       DRY RUN REPORT SUMMARY".bright_white().bold());
+    println!("{}", "═══════════════════════════════════════".bright_white());
+
+    println!("\n{}", "📁 Meta Files to Update:".bright_cyan().bold());
+    println!("   {} meta files will have their GUIDs changed", report.meta_files_changed());
+
+    println!("\n{}", "🔗 Reference Updates:".bright_cyan().bold());
+    println!("   {} total GUID references will be updated", report.total_references_replaced());
+    println!("   {} files contain references that need updating", report.files_with_references().len());
+    if !report.skipped_files().is_empty() {
+        println!("   {}", format!("{} files could not be read and were skipped", report.skipped_files().len()).bright_yellow());
+    }
+    if report.binary_files() > 0 {
+        println!("   {}", format!("{} binary files cannot be rewritten and were skipped", report.binary_files()).bright_red().bold());
+    }
+
+    println!("\n{}", "📦 Size of Modified Files:".bright_cyan().bold());
+    println!("   {}", report.size_line());
+
+    if !report.files_with_references().is_empty() {
+        println!("\n{}", "🔥 Busiest Files:".bright_cyan().bold());
+        let mut files: Vec<_> = report.files_with_references().iter().collect();
+        files.sort_by(|a, b| b.1.cmp(a.1).then_with(|| a.0.cmp(b.0)));
+        for (path, count) in files.iter().take(top_limit(top)) {
+            println!("   {} - {} references", path.display(), count);
+        }
+    }
+
+    if !report.guid_reference_counts().is_empty() {
+        println!("\n{}", "📊 Top Referenced GUIDs:".bright_cyan().bold());
+        let mut counts: Vec<_> = report.guid_reference_counts().iter().collect();
+        counts.sort_by(|a, b| b.1.cmp(a.1).then_with(|| a.0.cmp(b.0)));
+        for (guid, count) in counts.iter().take(top_limit(top)) {
+            println!("   {} - {} references", theme::old(guid), count);
+        }
+    }
+
+    println!("\n{}", "═══════════════════════════════════════".bright_white());
+    println!("{}", "To apply these changes, run without --dry-run flag".bright_green());
+    println!("{}", "WARNING: This will modify files in the subordinate project!".bright_red().bold());
+    println!("{}", "═══════════════════════════════════════".bright_white());
+}
+
+/// Print the collected diffs in path order, stopping once `max_lines` lines have been shown
+fn print_diffs(diffs: &[(PathBuf, String)], max_lines: usize) {
+    let mut diffs: Vec<&(PathBuf, String)> = diffs.iter().collect();
+    diffs.sort_by(|a, b| a.0.cmp(&b.0));
+
+    println!("\n{}", "Planned changes:".bright_white().bold());
+    let mut printed = 0;
+    let mut skipped = 0;
+    for line in diffs.iter().flat_map(|(_, diff)| diff.lines()) {
+        if printed >= max_lines {
+            skipped += 1;
+            continue;
+        }
+        printed += 1;
+        if line.starts_with("---") || line.starts_with("+++") {
+            println!("{}", line.bold());
+        } else if line.starts_with("@@") {
+            println!("{}", line.cyan());
+        } else if line.starts_with('-') {
+            println!("{}", line.red());
+        } else if line.starts_with('+') {
+            println!("{}", line.green());
+        } else {
+            println!("{}", line);
+        }
+    }
+
+    if skipped > 0 {
+        println!(
+            "{}",
+            format!("... {} more diff lines not shown (raise --max-diff-lines to see them)", skipped).bright_yellow()
+        );
+    }
+}
+
+/// The progress bar or spinner on screen, if any
+static PROGRESS: Mutex<Option<ProgressBar>> = Mutex::new(None);
+
+/// Put a new progress bar on screen in place of the last one. Progress is only drawn for
+/// interactive terminals.
+fn show_progress(bar: impl FnOnce() -> ProgressBar) {
+    if !std::io::stdout().is_terminal() {
+        return;
+    }
+    let bar = bar();
+    if let Some(previous) = PROGRESS.lock().unwrap_or_else(|e| e.into_inner()).replace(bar) {
+        previous.finish_and_clear();
+    }
+}
+
+fn advance_progress() {
+    if let Some(bar) = PROGRESS.lock().unwrap_or_else(|e| e.into_inner()).as_ref() {
+        bar.inc(1);
+    }
+}
+
+fn clear_progress() {
+    if let Some(bar) = PROGRESS.lock().unwrap_or_else(|e| e.into_inner()).take() {
+        bar.finish_and_clear();
+    }
+}
+
+/// Progress bar for work with a known number of files
+fn progress_bar(len: usize, message: &str) -> ProgressBar {
+    let bar = ProgressBar::new(len as u64);
+    bar.set_style(
+        ProgressStyle::with_template("{msg} [{bar:40.cyan/blue}] {pos}/{len} files")
+            .expect("valid progress template")
+            .progress_chars("=> "),
+    );
+    bar.set_message(message.to_string());
+    bar
+}
+
+/// Spinner for streaming walks where the total is not known up front
+fn spinner(message: &str) -> ProgressBar {
+    let spinner = ProgressBar::new_spinner();
+    spinner.set_style(ProgressStyle::with_template("{spinner} {msg} ({pos} files)").expect("valid progress template"));
+    spinner.set_message(message.to_string());
+    spinner.enable_steady_tick(Duration::from_millis(100));
+    spinner
+}
+
+/// Stderr for the log, with the progress bar cleared while a line is written so the two do not mix
+struct LogWriter;
+
+impl Write for LogWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let progress = PROGRESS.lock().unwrap_or_else(|e| e.into_inner()).clone();
+        match progress {
+            Some(bar) => bar.suspend(|| std::io::stderr().write(buf)),
+            None => std::io::stderr().write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        std::io::stderr().flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(unix)]
+    #[test]
+    fn symlinked_assets_into_the_other_project_is_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        let main = dir.path().join("Main");
+        let sub = dir.path().join("Sub");
+        fs::create_dir_all(main.join("Assets")).unwrap();
+        fs::create_dir_all(&sub).unwrap();
+        std::os::unix::fs::symlink(main.join("Assets"), sub.join("Assets")).unwrap();
+
+        let error = validate_paths(&main, &sub).unwrap_err().to_string();
+        assert!(error.contains("Subordinate Assets folder resolves to"), "{}", error);
+        assert!(validate_paths(&sub, &main).unwrap_err().to_string().contains("Main Assets folder resolves to"));
+
+        // A real Assets folder of its own is fine
+        fs::remove_file(sub.join("Assets")).unwrap();
+        fs::create_dir(sub.join("Assets")).unwrap();
+        validate_paths(&main, &sub).unwrap();
+    }
+}
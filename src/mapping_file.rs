@@ -0,0 +1,203 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::error::{GuidSyncError, IoContext, Result};
+use crate::guid_mapper::{ScanOptions, SyncOptions};
+use crate::path_filter::PathFilter;
+
+/// A single old -> new GUID pair from an externally provided mapping file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GuidPair {
+    pub old_guid: String,
+    pub new_guid: String,
+    /// New `type:` for `{fileID: ..., guid: ..., type: ...}` references to this GUID, for
+    /// assets whose import type changed (e.g. a texture that became a sprite)
+    #[serde(default, rename = "type", skip_serializing_if = "Option::is_none")]
+    pub new_type: Option<u32>,
+}
+
+/// The options of the sync that wrote a plan which decide what it touches, stored in the plan
+/// so `apply` selects and rewrites exactly the same files
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PlanOptions {
+    pub include: Vec<String>,
+    pub exclude: Vec<String>,
+    pub include_packages: bool,
+    pub include_project_settings: bool,
+    pub reference_extensions: Vec<String>,
+    pub follow_symlinks: bool,
+    pub try_encodings: bool,
+    pub skip_folders: bool,
+    pub rewrite_meta_refs: bool,
+    pub meta_only: bool,
+    pub refs_only: bool,
+}
+
+impl PlanOptions {
+    /// The scan options the plan was made with
+    pub fn scan_options(&self) -> Result<ScanOptions> {
+        Ok(ScanOptions {
+            filter: PathFilter::new(&self.include, &self.exclude)?,
+            include_packages: self.include_packages,
+            include_project_settings: self.include_project_settings,
+            reference_extensions: self.reference_extensions.clone(),
+            follow_symlinks: self.follow_symlinks,
+            try_encodings: self.try_encodings,
+            skip_folders: self.skip_folders,
+            ..Default::default()
+        })
+    }
+
+    /// Set the write options the plan was made with
+    pub fn apply_to(&self, options: &mut SyncOptions) {
+        options.rewrite_meta_refs = self.rewrite_meta_refs;
+        options.meta_only = self.meta_only;
+        options.refs_only = self.refs_only;
+    }
+}
+
+/// The pairs of a mapping file, and the options of the sync that planned them if it is a plan
+#[derive(Debug, Clone, Default)]
+pub struct Mapping {
+    pub pairs: Vec<GuidPair>,
+    pub options: Option<PlanOptions>,
+}
+
+/// A JSON mapping file: either the pairs themselves, or a plan written by `sync --plan`
+/// whose operations carry the same `old_guid`/`new_guid` fields
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum JsonMapping {
+    Pairs(Vec<GuidPair>),
+    Plan {
+        operations: Vec<GuidPair>,
+        #[serde(default)]
+        options: Option<PlanOptions>,
+    },
+}
+
+/// Load GUID pairs from a `.csv` file (`old_guid,new_guid[,type]` per line, optional header),
+/// a JSON array of `{"old_guid": ..., "new_guid": ..., "type": ...}` objects (`type` optional)
+/// or a `sync --plan` file.
+/// GUIDs are validated and normalized to lowercase; see `validate_pairs` for duplicates.
+pub fn load_guid_pairs(path: &Path) -> Result<Vec<GuidPair>> {
+    load_mapping(path).map(|mapping| mapping.pairs)
+}
+
+/// `load_guid_pairs`, also returning the options stored in a `sync --plan` file
+pub fn load_mapping(path: &Path) -> Result<Mapping> {
+    let content = fs::read_to_string(path).io_context(|| format!("Failed to read mapping file: {}", path.display()))?;
+
+    let is_csv = path
+        .extension()
+        .and_then(|s| s.to_str())
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("csv"));
+    let (pairs, options) = if is_csv {
+        (parse_csv(&content)?, None)
+    } else {
+        let parsed = serde_json::from_str(&content).map_err(|source| GuidSyncError::Json {
+            message: format!("Failed to parse mapping file: {}", path.display()),
+            source,
+        })?;
+        match parsed {
+            JsonMapping::Pairs(pairs) => (pairs, None),
+            JsonMapping::Plan { operations, options } => (operations, options),
+        }
+    };
+
+    Ok(Mapping { pairs: validate_pairs(pairs)?, options })
+}
+
+fn parse_csv(content: &str) -> Result<Vec<GuidPair>> {
+    let mut pairs = Vec::new();
+
+    for (index, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || (index == 0 && line.starts_with("old_guid")) {
+            continue;
+        }
+
+        let fields: Vec<&str> = line.split(',').map(|f| f.trim().trim_matches('"')).collect();
+        if fields.len() != 2 && fields.len() != 3 {
+            return Err(GuidSyncError::InvalidMappingLine {
+                line: index + 1,
+                reason: format!("expected `old_guid,new_guid[,type]`, got: {}", line),
+            });
+        }
+        let new_type = match fields.get(2).filter(|field| !field.is_empty()) {
+            Some(field) => Some(field.parse().map_err(|_| GuidSyncError::InvalidMappingLine {
+                line: index + 1,
+                reason: format!("invalid type {:?} (expected a non-negative number)", field),
+            })?),
+            None => None,
+        };
+        pairs.push(GuidPair {
+            old_guid: fields[0].to_string(),
+            new_guid: fields[1].to_string(),
+            new_type,
+        });
+    }
+
+    Ok(pairs)
+}
+
+/// Validate and lowercase the pairs. An old GUID listed again with the same new GUID and type
+/// is dropped, as in a plan where several meta files share a GUID; with a different new GUID
+/// or type it is an error.
+fn validate_pairs(pairs: Vec<GuidPair>) -> Result<Vec<GuidPair>> {
+    let mut seen: HashMap<String, (String, Option<u32>)> = HashMap::new();
+    let mut validated = Vec::with_capacity(pairs.len());
+
+    for pair in pairs {
+        for guid in [&pair.old_guid, &pair.new_guid] {
+            if !is_valid_guid(guid) {
+                return Err(GuidSyncError::InvalidMappingGuid(guid.clone()));
+            }
+        }
+
+        let old_guid = pair.old_guid.to_ascii_lowercase();
+        let new_guid = pair.new_guid.to_ascii_lowercase();
+        match seen.get(&old_guid) {
+            Some(earlier) if *earlier == (new_guid.clone(), pair.new_type) => continue,
+            Some(_) => return Err(GuidSyncError::ConflictingMapping(old_guid)),
+            None => {}
+        }
+        seen.insert(old_guid.clone(), (new_guid.clone(), pair.new_type));
+        validated.push(GuidPair { old_guid, new_guid, new_type: pair.new_type });
+    }
+
+    Ok(validated)
+}
+
+/// Load a path map: a JSON object of subordinate -> main paths relative to Assets, for assets
+/// that live at different paths in the two projects. Asset and meta paths are both accepted;
+/// both sides are returned as meta paths.
+pub fn load_path_map(path: &Path) -> Result<HashMap<PathBuf, PathBuf>> {
+    let content = fs::read_to_string(path).io_context(|| format!("Failed to read path map: {}", path.display()))?;
+    let entries: HashMap<String, String> = serde_json::from_str(&content).map_err(|source| GuidSyncError::Json {
+        message: format!("Failed to parse path map (expected a JSON object): {}", path.display()),
+        source,
+    })?;
+
+    Ok(entries
+        .into_iter()
+        .map(|(sub_path, main_path)| (meta_path(&sub_path), meta_path(&main_path)))
+        .collect())
+}
+
+/// `Foo.png` -> `Foo.png.meta`, leaving paths that already end in `.meta` alone
+fn meta_path(path: &str) -> PathBuf {
+    let path = path.trim_start_matches("./");
+    if path.ends_with(".meta") {
+        PathBuf::from(path)
+    } else {
+        PathBuf::from(format!("{}.meta", path))
+    }
+}
+
+pub fn is_valid_guid(guid: &str) -> bool {
+    guid.len() == 32 && guid.bytes().all(|b| b.is_ascii_hexdigit())
+}
@@ -1,50 +1,122 @@
-use anyhow::{Result, Context};
 use regex::Regex;
 use std::fs;
 use std::path::Path;
+use std::sync::LazyLock;
+
+use crate::atomic_write::write_atomic;
+use crate::error::{GuidSyncError, Result};
+
+const UTF8_BOM: &str = "\u{feff}";
+
+// Match guid line: can be with or without quotes, and may end in a comment from hand editing
+static GUID_LINE_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"^guid:\s*['"]?([a-fA-F0-9]{32})['"]?(\s*|\s+#.*)$"#).expect("valid GUID line regex")
+});
+
+// Any top-level guid field, whatever its value, to tell malformed GUIDs from missing ones
+static GUID_FIELD_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"^guid:\s*['"]?(.*?)['"]?(\s*|\s+#.*)$"#).expect("valid GUID field regex")
+});
+
+// Same as above, but capturing the surrounding format so it can be preserved
+static GUID_LINE_PARTS_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"^(guid:\s*)(['"]?)([a-fA-F0-9]{32})(['"]?)(\s*|\s+#.*)$"#).expect("valid GUID line regex")
+});
+
+// The importer block every meta file has at the top level, e.g. `TextureImporter:`
+static IMPORTER_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^([A-Za-z]*Importer):\s*$").expect("valid importer regex")
+});
 
 pub struct MetaFile;
 
+/// The parts of a meta file the syncer cares about
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MetaInfo {
+    pub guid: String,
+    /// Set for folder metas (`folderAsset: yes`)
+    pub is_folder: bool,
+    /// The importer that handles the asset, e.g. `TextureImporter` or `MonoImporter`
+    pub importer: Option<String>,
+}
+
 impl MetaFile {
-    /// Extract GUID from a meta file without parsing YAML
-    pub fn get_guid_from_file(path: &Path) -> Result<String> {
-        let content = fs::read_to_string(path)
-            .with_context(|| format!("Failed to read meta file: {}", path.display()))?;
-        
-        // Match guid line: can be with or without quotes
-        let guid_regex = Regex::new(r#"^guid:\s*['"]?([a-f0-9]{32})['"]?\s*$"#)?;
+    /// Extract the GUID, folder flag and importer from a meta file without parsing YAML
+    pub fn parse(path: &Path) -> Result<MetaInfo> {
+        let content = read_meta(path)?;
+        // A leading BOM would otherwise stop the anchored regex from matching the first line
+        let content = content.strip_prefix(UTF8_BOM).unwrap_or(&content);
         
+        let mut guid = None;
+        let mut malformed = None;
+        let mut is_folder = false;
+        let mut importer = None;
         for line in content.lines() {
-            if let Some(captures) = guid_regex.captures(line) {
-                if let Some(guid) = captures.get(1) {
-                    return Ok(guid.as_str().to_string());
+            if guid.is_none() {
+                if let Some(captures) = GUID_LINE_REGEX.captures(line) {
+                    guid = captures.get(1).map(|m| m.as_str().to_string());
+                } else if let Some(captures) = GUID_FIELD_REGEX.captures(line) {
+                    malformed.get_or_insert_with(|| captures[1].to_string());
                 }
             }
+            if line.trim_end() == "folderAsset: yes" {
+                is_folder = true;
+            }
+            if importer.is_none() {
+                importer = IMPORTER_REGEX.captures(line).map(|captures| captures[1].to_string());
+            }
         }
         
-        anyhow::bail!("No GUID found in meta file: {}", path.display())
+        match (guid, malformed) {
+            (Some(guid), _) => Ok(MetaInfo { guid, is_folder, importer }),
+            (None, Some(value)) => Err(GuidSyncError::MalformedGuid { path: path.to_path_buf(), value }),
+            (None, None) => Err(GuidSyncError::MissingGuid(path.to_path_buf())),
+        }
+    }
+    
+    /// Extract GUID from a meta file without parsing YAML. Only the unindented top-level `guid:`
+    /// counts; nested IDs such as a sprite sheet's `spriteID` or `internalID` are never taken.
+    pub fn get_guid_from_file(path: &Path) -> Result<String> {
+        Self::parse(path).map(|info| info.guid)
     }
     
     /// Update only the GUID in a meta file, preserving all formatting
     pub fn update_guid_in_file(path: &Path, new_guid: &str) -> Result<()> {
-        let content = fs::read_to_string(path)
-            .with_context(|| format!("Failed to read meta file: {}", path.display()))?;
+        let content = read_meta(path)?;
+        let Some(new_content) = Self::rewrite_guid(&content, new_guid) else {
+            return Err(GuidSyncError::MissingGuid(path.to_path_buf()));
+        };
         
-        // Match guid line: can be with or without quotes, preserve the format
-        let guid_regex = Regex::new(r#"^(guid:\s*)(['"]?)([a-f0-9]{32})(['"]?)\s*$"#)?;
+        write_atomic(path, new_content.as_bytes())
+    }
+    
+    /// Meta file content with its GUID line replaced, or None if it has no GUID line
+    pub fn rewrite_guid(content: &str, new_guid: &str) -> Option<String> {
+        // Strip a leading BOM for matching, and put it back on write
+        let (bom, content) = match content.strip_prefix(UTF8_BOM) {
+            Some(rest) => (UTF8_BOM, rest),
+            None => ("", content),
+        };
         
         let mut updated = false;
-        let new_content: Vec<String> = content
-            .lines()
+        // Split keeping each line's terminator, so LF and CRLF endings survive byte-for-byte
+        let new_content: String = content
+            .split_inclusive('\n')
             .map(|line| {
-                if let Some(captures) = guid_regex.captures(line) {
+                let body = line.trim_end_matches(['\r', '\n']);
+                let line_ending = &line[body.len()..];
+                // Only the asset's own GUID, should a hand-edited meta repeat the key
+                let captures = if updated { None } else { GUID_LINE_PARTS_REGEX.captures(body) };
+                if let Some(captures) = captures {
                     updated = true;
-                    // Preserve the original formatting (quotes or no quotes)
-                    format!("{}{}{}{}",
+                    // Preserve the original formatting: spacing, quotes and anything trailing
+                    format!("{}{}{}{}{}{}",
                         captures.get(1).map_or("", |m| m.as_str()),
                         captures.get(2).map_or("", |m| m.as_str()),
                         new_guid,
-                        captures.get(4).map_or("", |m| m.as_str())
+                        captures.get(4).map_or("", |m| m.as_str()),
+                        captures.get(5).map_or("", |m| m.as_str()),
+                        line_ending
                     )
                 } else {
                     line.to_string()
@@ -52,20 +124,14 @@ impl MetaFile {
             })
             .collect();
         
-        if !updated {
-            anyhow::bail!("No GUID found to update in meta file: {}", path.display())
-        }
-        
-        // Join with newlines and add final newline if original had one
-        let final_content = if content.ends_with('\n') {
-            new_content.join("\n") + "\n"
-        } else {
-            new_content.join("\n")
-        };
-        
-        fs::write(path, final_content)
-            .with_context(|| format!("Failed to write meta file: {}", path.display()))?;
-        
-        Ok(())
+        updated.then(|| format!("{}{}", bom, new_content))
     }
-}
\ No newline at end of file
+}
+
+/// A meta file's contents, telling a missing meta file apart from one that cannot be read
+fn read_meta(path: &Path) -> Result<String> {
+    fs::read_to_string(path).map_err(|source| match source.kind() {
+        std::io::ErrorKind::NotFound => GuidSyncError::MetaNotFound(path.to_path_buf()),
+        _ => GuidSyncError::Io { message: format!("Failed to read meta file: {}", path.display()), source },
+    })
+}
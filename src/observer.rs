@@ -0,0 +1,54 @@
+use std::path::Path;
+
+use crate::guid_mapper::{GuidChange, SyncOptions, SyncReport};
+
+/// Hooks that [`GuidSyncer`](crate::GuidSyncer) calls while it scans and syncs, so embedders
+/// can drive their own progress display. Every method does nothing by default. Unless noted
+/// otherwise they are called in path order from the thread that started the scan or sync.
+pub trait SyncObserver: Send + Sync {
+    /// The meta files of `project` are about to be scanned
+    fn on_scan_started(&self, _project: &Path) {}
+
+    /// A meta file was read while scanning a project; `meta_path` is relative to it
+    fn on_file_scanned(&self, _meta_path: &Path) {}
+
+    /// The scan of `project` finished after reading `files` meta files
+    fn on_scan_finished(&self, _project: &Path, _files: usize) {}
+
+    /// The subordinate's reference files are about to be read, see [`ReferencePass`]
+    fn on_references_started(&self, _pass: ReferencePass) {}
+
+    /// One more reference file was read, and rewritten if it had to be. During a sync this is
+    /// called from the worker threads, in no particular order.
+    fn on_reference_file_read(&self) {}
+
+    /// The pass over the reference files finished
+    fn on_references_finished(&self) {}
+
+    /// A subordinate meta file got its new GUID, or would have in a dry run
+    fn on_meta_updated(&self, _change: &GuidChange) {}
+
+    /// `count` GUID references were rewritten in `file`, or would have been in a dry run;
+    /// `file` is relative to the project
+    fn on_reference_replaced(&self, _file: &Path, _count: usize) {}
+
+    /// A sync was started without any GUID differences to resolve, so it did nothing
+    fn on_nothing_to_sync(&self) {}
+
+    /// A sync finished; `report` holds what it changed, or would have in a dry run
+    fn on_sync_finished(&self, _report: &SyncReport, _options: &SyncOptions) {}
+}
+
+/// What a pass over the subordinate's reference files is for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReferencePass {
+    /// A sync rewriting the references in `files` files
+    Rewrite { files: usize },
+    /// An operations report counting the references in every reference file, however many
+    Count,
+}
+
+/// The observer of a syncer nobody is watching
+pub(crate) struct NoObserver;
+
+impl SyncObserver for NoObserver {}
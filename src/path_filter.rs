@@ -0,0 +1,58 @@
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+use std::path::Path;
+
+use crate::error::{GuidSyncError, Result};
+
+/// Include/exclude glob filter applied to paths relative to the Assets root
+#[derive(Debug, Clone, Default)]
+pub struct PathFilter {
+    includes: Option<GlobSet>,
+    excludes: Option<GlobSet>,
+}
+
+impl PathFilter {
+    /// Build a filter from include and exclude globs. With no includes everything is
+    /// included; excludes always win over includes.
+    pub fn new(includes: &[String], excludes: &[String]) -> Result<Self> {
+        Ok(Self {
+            includes: build_glob_set(includes)?,
+            excludes: build_glob_set(excludes)?,
+        })
+    }
+
+    pub fn is_match(&self, relative_path: &Path) -> bool {
+        if let Some(excludes) = &self.excludes {
+            if excludes.is_match(relative_path) {
+                return false;
+            }
+        }
+        match &self.includes {
+            Some(includes) => includes.is_match(relative_path),
+            None => true,
+        }
+    }
+
+    /// Match a meta file by the asset it describes, so `*.png` also selects `Foo.png.meta`
+    pub fn is_meta_match(&self, relative_meta_path: &Path) -> bool {
+        self.is_match(&relative_meta_path.with_extension(""))
+    }
+}
+
+fn build_glob_set(patterns: &[String]) -> Result<Option<GlobSet>> {
+    if patterns.is_empty() {
+        return Ok(None);
+    }
+
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        let glob = GlobBuilder::new(pattern)
+            .literal_separator(true)
+            .build()
+            .map_err(|source| GuidSyncError::InvalidGlob { pattern: pattern.clone(), source })?;
+        builder.add(glob);
+    }
+    let set = builder
+        .build()
+        .map_err(|source| GuidSyncError::InvalidGlob { pattern: patterns.join(", "), source })?;
+    Ok(Some(set))
+}
@@ -0,0 +1,72 @@
+use std::fs::{self, File};
+use std::io;
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+
+use crate::error::{GuidSyncError, IoContext, Result};
+
+/// A zipped Unity project extracted to a temp dir, which is removed again when this is dropped
+pub struct ProjectArchive {
+    _dir: TempDir,
+    root: PathBuf,
+}
+
+/// Whether `path` is a zip file rather than a project folder
+pub fn is_archive(path: &Path) -> bool {
+    path.is_file() && path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("zip"))
+}
+
+impl ProjectArchive {
+    /// Extract the entries of the zip at `path` for which `keep` returns true. The project root
+    /// is the folder holding `Assets`: either the archive root or its single top-level folder.
+    pub fn extract(path: &Path, keep: impl Fn(&Path) -> bool) -> Result<Self> {
+        let file = File::open(path).io_context(|| format!("Failed to open archive: {}", path.display()))?;
+        let read_error = |source| GuidSyncError::Archive { message: format!("Failed to read archive: {}", path.display()), source };
+        let mut archive = zip::ZipArchive::new(file).map_err(read_error)?;
+        let dir = tempfile::Builder::new()
+            .prefix("guid-sync-")
+            .tempdir()
+            .io_context(|| "Failed to create a temp dir for the archive".to_string())?;
+
+        for index in 0..archive.len() {
+            let mut entry = archive.by_index(index).map_err(read_error)?;
+            // Entries that would land outside the temp dir (`../`, absolute paths) are skipped
+            let Some(relative_path) = entry.enclosed_name() else {
+                tracing::warn!("Skipping unsafe archive entry: {}", entry.name());
+                continue;
+            };
+            if entry.is_dir() || !keep(&relative_path) {
+                continue;
+            }
+
+            let target = dir.path().join(&relative_path);
+            if let Some(parent) = target.parent() {
+                fs::create_dir_all(parent).io_context(|| format!("Failed to extract {}", relative_path.display()))?;
+            }
+            let mut out = File::create(&target).io_context(|| format!("Failed to extract {}", relative_path.display()))?;
+            io::copy(&mut entry, &mut out).io_context(|| format!("Failed to extract {}", relative_path.display()))?;
+        }
+
+        let root = find_project_root(dir.path()).ok_or_else(|| GuidSyncError::ArchiveWithoutAssets(path.to_path_buf()))?;
+        tracing::debug!("Extracted {} to {}", path.display(), root.display());
+        Ok(Self { _dir: dir, root })
+    }
+
+    /// The extracted project folder, to be used like any project path
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+}
+
+fn find_project_root(dir: &Path) -> Option<PathBuf> {
+    if dir.join("Assets").is_dir() {
+        return Some(dir.to_path_buf());
+    }
+    let mut folders = fs::read_dir(dir).ok()?.filter_map(|entry| entry.ok()).filter(|entry| entry.path().is_dir());
+    let single = folders.next()?;
+    if folders.next().is_some() {
+        return None;
+    }
+    let root = single.path();
+    root.join("Assets").is_dir().then_some(root)
+}
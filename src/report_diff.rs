@@ -0,0 +1,171 @@
+use colored::*;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::error::{GuidSyncError, IoContext, Result};
+use crate::guid_mapper::{SyncOperation, SyncOperationsReport};
+use crate::slash_path;
+use crate::theme;
+
+/// How the operations of a report changed since an earlier one. Operations are the same if
+/// they rewrite the same asset from the same old GUID to the same new GUID.
+#[derive(Debug, Serialize)]
+pub struct ReportDiff {
+    pub added: Vec<OperationEntry>,
+    pub removed: Vec<OperationEntry>,
+    /// Operations in both reports whose reference count changed
+    pub changed: Vec<ChangedOperation>,
+    pub old_operations: usize,
+    pub new_operations: usize,
+    pub old_references: usize,
+    pub new_references: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct OperationEntry {
+    #[serde(serialize_with = "slash_path::serialize")]
+    pub asset_path: PathBuf,
+    pub old_guid: String,
+    pub new_guid: String,
+    pub total_references: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ChangedOperation {
+    #[serde(serialize_with = "slash_path::serialize")]
+    pub asset_path: PathBuf,
+    pub old_guid: String,
+    pub new_guid: String,
+    pub old_references: usize,
+    pub new_references: usize,
+}
+
+type OperationKey<'a> = (&'a Path, &'a str, &'a str);
+
+fn by_key(report: &SyncOperationsReport) -> BTreeMap<OperationKey<'_>, &SyncOperation> {
+    report
+        .operations
+        .iter()
+        .map(|op| ((op.asset_path.as_path(), op.old_guid.as_str(), op.new_guid.as_str()), op))
+        .collect()
+}
+
+impl OperationEntry {
+    fn from_operation(op: &SyncOperation) -> Self {
+        Self {
+            asset_path: op.asset_path.clone(),
+            old_guid: op.old_guid.clone(),
+            new_guid: op.new_guid.clone(),
+            total_references: op.total_references,
+        }
+    }
+}
+
+impl ReportDiff {
+    /// Compare two operations reports, each list sorted by asset path
+    pub fn between(old: &SyncOperationsReport, new: &SyncOperationsReport) -> Self {
+        let old_ops = by_key(old);
+        let new_ops = by_key(new);
+
+        let added = new_ops
+            .iter()
+            .filter(|(key, _)| !old_ops.contains_key(*key))
+            .map(|(_, op)| OperationEntry::from_operation(op))
+            .collect();
+        let removed = old_ops
+            .iter()
+            .filter(|(key, _)| !new_ops.contains_key(*key))
+            .map(|(_, op)| OperationEntry::from_operation(op))
+            .collect();
+        let changed = old_ops
+            .iter()
+            .filter_map(|(key, old_op)| {
+                let new_op = new_ops.get(key)?;
+                (old_op.total_references != new_op.total_references).then(|| ChangedOperation {
+                    asset_path: old_op.asset_path.clone(),
+                    old_guid: old_op.old_guid.clone(),
+                    new_guid: old_op.new_guid.clone(),
+                    old_references: old_op.total_references,
+                    new_references: new_op.total_references,
+                })
+            })
+            .collect();
+
+        Self {
+            added,
+            removed,
+            changed,
+            old_operations: old.operations.len(),
+            new_operations: new.operations.len(),
+            old_references: old.summary.total_reference_updates,
+            new_references: new.summary.total_reference_updates,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+
+    pub fn print(&self) {
+        println!(
+            "Operations: {} -> {}, references: {} -> {}",
+            self.old_operations, self.new_operations, self.old_references, self.new_references
+        );
+        if self.is_empty() {
+            println!("{}", "The plans are identical".green());
+            return;
+        }
+
+        if !self.added.is_empty() {
+            println!("\n{}", format!("Added ({}):", self.added.len()).bright_white().underline());
+            for op in &self.added {
+                println!("  {} {}", "+".green(), entry_line(op));
+            }
+        }
+        if !self.removed.is_empty() {
+            println!("\n{}", format!("Removed ({}):", self.removed.len()).bright_white().underline());
+            for op in &self.removed {
+                println!("  {} {}", "-".red(), entry_line(op));
+            }
+        }
+        if !self.changed.is_empty() {
+            println!("\n{}", format!("Reference counts changed ({}):", self.changed.len()).bright_white().underline());
+            for op in &self.changed {
+                let delta = op.new_references as i64 - op.old_references as i64;
+                println!(
+                    "  {} {} -> {}: {} -> {} references ({:+})",
+                    op.asset_path.display().to_string().bright_cyan(),
+                    theme::old(&op.old_guid),
+                    theme::new(&op.new_guid),
+                    op.old_references,
+                    op.new_references,
+                    delta
+                );
+            }
+        }
+    }
+}
+
+fn entry_line(op: &OperationEntry) -> String {
+    format!(
+        "{} {} -> {} ({} references)",
+        op.asset_path.display().to_string().bright_cyan(),
+        theme::old(&op.old_guid),
+        theme::new(&op.new_guid),
+        op.total_references
+    )
+}
+
+impl SyncOperationsReport {
+    /// Read a report written by `report --format json`
+    pub fn load_from_file(path: &Path) -> Result<Self> {
+        let json = fs::read_to_string(path)
+            .io_context(|| format!("Failed to read operations report: {}", path.display()))?;
+        serde_json::from_str(&json).map_err(|source| GuidSyncError::Json {
+            message: format!("Failed to parse operations report (only JSON reports can be compared): {}", path.display()),
+            source,
+        })
+    }
+}
@@ -0,0 +1,146 @@
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::path::PathBuf;
+
+use crate::error::{GuidSyncError, Result};
+use crate::guid_mapper::SyncOperationsReport;
+
+/// File format of a written operations report
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum ReportFormat {
+    /// Full report as pretty-printed JSON
+    #[default]
+    Json,
+    /// JSON Lines: the summary on the first line, then one operation per line
+    Jsonl,
+    /// One row per operation: asset, old GUID, new GUID, total references, risk, importer
+    Csv,
+    /// Summary, operations table and the most referencing files
+    Markdown,
+}
+
+impl ReportFormat {
+    /// File extension of a report written in this format, without the dot
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Json => "json",
+            Self::Jsonl => "jsonl",
+            Self::Csv => "csv",
+            Self::Markdown => "md",
+        }
+    }
+}
+
+/// Number of files listed in the Markdown "top references" section
+const TOP_REFERENCES: usize = 10;
+
+impl SyncOperationsReport {
+    pub fn render(&self, format: ReportFormat) -> Result<String> {
+        match format {
+            ReportFormat::Json => serde_json::to_string_pretty(self).map_err(render_error),
+            ReportFormat::Jsonl => self.to_jsonl(),
+            ReportFormat::Csv => Ok(self.to_csv()),
+            ReportFormat::Markdown => Ok(self.to_markdown()),
+        }
+    }
+
+    fn to_jsonl(&self) -> Result<String> {
+        let mut jsonl = serde_json::to_string(&self.summary).map_err(render_error)?;
+        jsonl.push('\n');
+        for op in &self.operations {
+            jsonl.push_str(&serde_json::to_string(op).map_err(render_error)?);
+            jsonl.push('\n');
+        }
+        Ok(jsonl)
+    }
+
+    fn to_csv(&self) -> String {
+        let mut csv = String::from("asset_name,asset_path,old_guid,new_guid,total_references,risk,importer\n");
+        for op in &self.operations {
+            let _ = writeln!(
+                csv,
+                "{},{},{},{},{},{},{}",
+                csv_field(&op.asset_name),
+                csv_field(&op.asset_path.display().to_string()),
+                op.old_guid,
+                op.new_guid,
+                op.total_references,
+                op.risk.as_str(),
+                csv_field(&op.asset_importer)
+            );
+        }
+        csv
+    }
+
+    fn to_markdown(&self) -> String {
+        let mut md = String::from("# GUID Sync Operations Report\n\n");
+        let summary = &self.summary;
+        let _ = writeln!(md, "- GUIDs to change: {}", summary.total_guid_differences);
+        let _ = writeln!(md, "- Meta files to update: {}", summary.total_meta_files_to_update);
+        let _ = writeln!(md, "- Files with references: {}", summary.total_files_with_references);
+        let _ = writeln!(md, "- Total reference updates: {}", summary.total_reference_updates);
+
+        md.push_str("\n## Operations\n\n");
+        md.push_str("| Asset | Importer | Path | Old GUID | New GUID | References | Risk |\n");
+        md.push_str("|---|---|---|---|---|---:|---|\n");
+        for op in &self.operations {
+            let _ = writeln!(
+                md,
+                "| {} | {} | `{}` | `{}` | `{}` | {} | {} |",
+                markdown_cell(&op.asset_name),
+                markdown_cell(&op.asset_importer),
+                markdown_cell(&op.asset_path.display().to_string()),
+                op.old_guid,
+                op.new_guid,
+                op.total_references,
+                op.risk.as_str()
+            );
+        }
+
+        let mut per_file: HashMap<&PathBuf, usize> = HashMap::new();
+        for update in self.operations.iter().flat_map(|op| &op.reference_updates) {
+            *per_file.entry(&update.file_path).or_insert(0) += update.reference_count;
+        }
+        let mut per_file: Vec<_> = per_file.into_iter().collect();
+        per_file.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+
+        if !self.dangling_references.is_empty() {
+            md.push_str("\n## Dangling References\n\n");
+            md.push_str("| GUID | File | References |\n");
+            md.push_str("|---|---|---:|\n");
+            for dangling in &self.dangling_references {
+                for (file, count) in &dangling.files {
+                    let _ = writeln!(md, "| `{}` | `{}` | {} |", dangling.guid, markdown_cell(&file.display().to_string()), count);
+                }
+            }
+        }
+
+        if !per_file.is_empty() {
+            md.push_str("\n## Top References\n\n");
+            md.push_str("| File | References |\n");
+            md.push_str("|---|---:|\n");
+            for (file, count) in per_file.iter().take(TOP_REFERENCES) {
+                let _ = writeln!(md, "| `{}` | {} |", markdown_cell(&file.display().to_string()), count);
+            }
+        }
+        md
+    }
+}
+
+fn render_error(source: serde_json::Error) -> GuidSyncError {
+    GuidSyncError::Json { message: "Failed to render the operations report".to_string(), source }
+}
+
+/// Quote a CSV field if it contains a separator, quote or line break
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Escape the pipes that would otherwise split a Markdown table cell
+fn markdown_cell(value: &str) -> String {
+    value.replace('|', "\\|")
+}
@@ -0,0 +1,205 @@
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Paragraph, Row, Table, TableState, Wrap};
+use ratatui::{DefaultTerminal, Frame};
+use std::io::IsTerminal;
+
+use crate::error::{GuidSyncError, IoContext, Result};
+use crate::guid_mapper::{RiskLevel, SyncOperation, SyncOperationsReport};
+
+/// Order of the operations list, switched with `s`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SortOrder {
+    /// As in the report: riskiest first, then most referenced
+    Report,
+    MostReferenced,
+    LeastReferenced,
+}
+
+impl SortOrder {
+    fn next(self) -> Self {
+        match self {
+            Self::Report => Self::MostReferenced,
+            Self::MostReferenced => Self::LeastReferenced,
+            Self::LeastReferenced => Self::Report,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Self::Report => "risk",
+            Self::MostReferenced => "most references",
+            Self::LeastReferenced => "fewest references",
+        }
+    }
+}
+
+struct App<'a> {
+    report: &'a SyncOperationsReport,
+    /// Indices into `report.operations` in display order
+    order: Vec<usize>,
+    sort: SortOrder,
+    table: TableState,
+}
+
+/// Browse the operations of a report in a read-only terminal UI until the user quits
+pub fn run(report: &SyncOperationsReport) -> Result<()> {
+    if !std::io::stdout().is_terminal() {
+        return Err(GuidSyncError::NotATerminal);
+    }
+
+    let mut app = App {
+        report,
+        order: (0..report.operations.len()).collect(),
+        sort: SortOrder::Report,
+        table: TableState::default(),
+    };
+    if !report.operations.is_empty() {
+        app.table.select(Some(0));
+    }
+
+    let mut terminal = ratatui::init();
+    let result = app.event_loop(&mut terminal);
+    ratatui::restore();
+    result
+}
+
+impl App<'_> {
+    fn event_loop(&mut self, terminal: &mut DefaultTerminal) -> Result<()> {
+        loop {
+            terminal
+                .draw(|frame| self.draw(frame))
+                .io_context(|| "Failed to draw the terminal UI".to_string())?;
+            let Event::Key(key) = event::read().io_context(|| "Failed to read a key press".to_string())? else {
+                continue;
+            };
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+            match key.code {
+                KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+                KeyCode::Down | KeyCode::Char('j') => self.table.select_next(),
+                KeyCode::Up | KeyCode::Char('k') => self.table.select_previous(),
+                KeyCode::PageDown => self.table.scroll_down_by(10),
+                KeyCode::PageUp => self.table.scroll_up_by(10),
+                KeyCode::Home | KeyCode::Char('g') => self.table.select_first(),
+                KeyCode::End | KeyCode::Char('G') => self.table.select_last(),
+                KeyCode::Char('s') => self.resort(),
+                _ => {}
+            }
+        }
+    }
+
+    /// Switch to the next sort order, keeping the selected operation selected
+    fn resort(&mut self) {
+        let selected = self.selected_index();
+        self.sort = self.sort.next();
+        let operations = &self.report.operations;
+        match self.sort {
+            SortOrder::Report => self.order.sort(),
+            SortOrder::MostReferenced => self
+                .order
+                .sort_by_key(|&i| (std::cmp::Reverse(operations[i].total_references), i)),
+            SortOrder::LeastReferenced => self.order.sort_by_key(|&i| (operations[i].total_references, i)),
+        }
+        if let Some(selected) = selected {
+            self.table.select(self.order.iter().position(|&i| i == selected));
+        }
+    }
+
+    fn selected_index(&self) -> Option<usize> {
+        self.table.selected().and_then(|row| self.order.get(row).copied())
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let [header, body, footer] =
+            Layout::vertical([Constraint::Length(1), Constraint::Min(0), Constraint::Length(1)]).areas(frame.area());
+        let [list, detail] =
+            Layout::horizontal([Constraint::Percentage(55), Constraint::Percentage(45)]).areas(body);
+
+        let summary = &self.report.summary;
+        frame.render_widget(
+            Line::from(format!(
+                " {} GUIDs to change, {} references in {} files",
+                summary.total_guid_differences, summary.total_reference_updates, summary.total_files_with_references
+            ))
+            .style(Style::new().add_modifier(Modifier::BOLD)),
+            header,
+        );
+
+        let rows = self.order.iter().map(|&i| {
+            let op = &self.report.operations[i];
+            Row::new(vec![
+                Span::raw(op.asset_name.clone()),
+                Span::raw(op.old_guid[..8].to_string()),
+                Span::raw(op.new_guid[..8].to_string()),
+                Span::raw(op.total_references.to_string()),
+                Span::styled(op.risk.as_str(), risk_style(op.risk)),
+            ])
+        });
+        let table = Table::new(
+            rows,
+            [
+                Constraint::Min(12),
+                Constraint::Length(8),
+                Constraint::Length(8),
+                Constraint::Length(10),
+                Constraint::Length(6),
+            ],
+        )
+        .header(Row::new(["Asset", "Old", "New", "References", "Risk"]).style(Style::new().add_modifier(Modifier::BOLD)))
+        .row_highlight_style(Style::new().add_modifier(Modifier::REVERSED))
+        .block(Block::default().borders(Borders::ALL).title(format!(" Operations (sorted by {}) ", self.sort.label())));
+        frame.render_stateful_widget(table, list, &mut self.table);
+
+        let detail_lines = match self.selected_index() {
+            Some(i) => operation_detail(&self.report.operations[i]),
+            None => vec![Line::from("No GUID differences")],
+        };
+        frame.render_widget(
+            Paragraph::new(detail_lines)
+                .wrap(Wrap { trim: false })
+                .block(Block::default().borders(Borders::ALL).title(" Affected files ")),
+            detail,
+        );
+
+        frame.render_widget(
+            Line::from(" ↑/↓ select  PgUp/PgDn scroll  s sort  q quit").style(Style::new().fg(Color::DarkGray)),
+            footer,
+        );
+    }
+}
+
+fn operation_detail(op: &SyncOperation) -> Vec<Line<'static>> {
+    let mut lines = vec![
+        Line::from(Span::styled(op.asset_path.display().to_string(), Style::new().add_modifier(Modifier::BOLD))),
+        Line::from(format!("Importer: {}", if op.asset_importer.is_empty() { "unknown" } else { &op.asset_importer })),
+        Line::from(format!("Old GUID: {}", op.old_guid)),
+        Line::from(format!("New GUID: {}", op.new_guid)),
+        Line::from(vec![Span::raw("Risk: "), Span::styled(op.risk.as_str(), risk_style(op.risk))]),
+        Line::from(""),
+    ];
+    if op.reference_updates.is_empty() {
+        lines.push(Line::from("Only the meta file changes, nothing references this GUID"));
+    }
+    for update in &op.reference_updates {
+        lines.push(Line::from(format!("{} - {} references", update.file_path.display(), update.reference_count)));
+        for detail in update.references.iter().flatten() {
+            lines.push(
+                Line::from(format!("  {}: {}", detail.line_number, detail.context_snippet))
+                    .style(Style::new().fg(Color::DarkGray)),
+            );
+        }
+    }
+    lines
+}
+
+fn risk_style(risk: RiskLevel) -> Style {
+    match risk {
+        RiskLevel::High => Style::new().fg(Color::Red),
+        RiskLevel::Medium => Style::new().fg(Color::Yellow),
+        RiskLevel::Low => Style::new().fg(Color::Green),
+    }
+}
@@ -0,0 +1,61 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use crate::error::{GuidSyncError, IoContext, Result};
+
+/// A main project scan saved to disk, so unchanged meta files need not be read again
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ScanCache {
+    /// The project the entries belong to; a cache for another project is ignored
+    pub project: PathBuf,
+    pub entries: HashMap<PathBuf, CachedMeta>, // relative meta path -> scan result
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CachedMeta {
+    pub guid: String,
+    pub is_folder: bool,
+    /// Modification time of the meta file when it was read
+    pub modified: SystemTime,
+}
+
+impl ScanCache {
+    /// Load the cache for `project`, starting empty if the file does not exist yet or
+    /// was written for a different project
+    pub fn load(path: &Path, project: &Path) -> Result<Self> {
+        let project = canonical(project);
+        if !path.exists() {
+            return Ok(Self { project, entries: HashMap::new() });
+        }
+
+        let json = fs::read_to_string(path).io_context(|| format!("Failed to read scan cache: {}", path.display()))?;
+        let cache: Self = serde_json::from_str(&json).map_err(|source| GuidSyncError::Json {
+            message: format!("Failed to parse scan cache: {}", path.display()),
+            source,
+        })?;
+        if cache.project != project {
+            return Ok(Self { project, entries: HashMap::new() });
+        }
+        Ok(cache)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let json = serde_json::to_string(self).map_err(|source| GuidSyncError::Json {
+            message: format!("Failed to serialize scan cache: {}", path.display()),
+            source,
+        })?;
+        fs::write(path, json).io_context(|| format!("Failed to write scan cache: {}", path.display()))
+    }
+
+    /// The cached entry for a meta file, if it has not been modified since it was read
+    pub fn get(&self, rel_path: &Path, modified: SystemTime) -> Option<&CachedMeta> {
+        self.entries.get(rel_path).filter(|entry| entry.modified == modified)
+    }
+}
+
+fn canonical(path: &Path) -> PathBuf {
+    path.canonicalize().unwrap_or_else(|_| path.to_path_buf())
+}
@@ -0,0 +1,62 @@
+use serde::{Deserialize, Deserializer, Serializer};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+/// Serialize a path with `/` separators, so reports are identical whichever OS wrote them
+pub fn serialize<S: Serializer>(path: &Path, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&to_slashes(path))
+}
+
+/// Serialize a set of paths as a sorted list with `/` separators
+pub fn serialize_set<S: Serializer>(paths: &HashSet<PathBuf>, serializer: S) -> Result<S::Ok, S::Error> {
+    let mut paths: Vec<String> = paths.iter().map(|path| to_slashes(path)).collect();
+    paths.sort();
+    serializer.collect_seq(paths)
+}
+
+/// Serialize per-path counts as an object sorted by path, with `/` separators
+pub fn serialize_counts<S: Serializer>(counts: &HashMap<PathBuf, usize>, serializer: S) -> Result<S::Ok, S::Error> {
+    let counts: BTreeMap<String, usize> = counts.iter().map(|(path, count)| (to_slashes(path), *count)).collect();
+    serializer.collect_map(counts)
+}
+
+/// Read per-path counts, also accepting the plain list of paths older reports wrote (counted as 0)
+pub fn deserialize_counts<'de, D: Deserializer<'de>>(deserializer: D) -> Result<HashMap<PathBuf, usize>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Counts {
+        Map(HashMap<PathBuf, usize>),
+        List(Vec<PathBuf>),
+    }
+    Ok(match Counts::deserialize(deserializer)? {
+        Counts::Map(counts) => counts,
+        Counts::List(paths) => paths.into_iter().map(|path| (path, 0)).collect(),
+    })
+}
+
+fn to_slashes(path: &Path) -> String {
+    path.to_string_lossy().replace('\\', "/")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::guid_mapper::ReferenceUpdate;
+
+    #[test]
+    fn windows_path_serializes_with_slashes() {
+        let update = ReferenceUpdate {
+            file_path: PathBuf::from(r"Scenes\Levels\Level.unity"),
+            file_type: "unity".to_string(),
+            reference_count: 1,
+            references: None,
+        };
+        let json = serde_json::to_value(&update).unwrap();
+        assert_eq!(json["file_path"], "Scenes/Levels/Level.unity");
+
+        let counts = HashMap::from([(PathBuf::from(r"Prefabs\Player.prefab"), 2)]);
+        let mut serializer = serde_json::Serializer::new(Vec::new());
+        serialize_counts(&counts, &mut serializer).unwrap();
+        assert_eq!(String::from_utf8(serializer.into_inner()).unwrap(), r#"{"Prefabs/Player.prefab":2}"#);
+    }
+}
@@ -0,0 +1,72 @@
+use encoding_rs::{Encoding, UTF_16BE, UTF_16LE, WINDOWS_1252};
+use std::borrow::Cow;
+
+/// Number of leading bytes looked at to tell UTF-16 without a BOM from single-byte text
+const SNIFF_LENGTH: usize = 512;
+
+/// Encoding of a text file that is not plain UTF-8, kept so the file can be written back as it was
+#[derive(Debug, Clone, Copy)]
+pub struct TextEncoding {
+    encoding: &'static Encoding,
+    bom_length: usize,
+}
+
+impl TextEncoding {
+    /// Detect the encoding from a byte order mark, else from the zero bytes that ASCII text has
+    /// in UTF-16, falling back to Latin-1 (as Windows-1252, which maps every byte)
+    pub fn detect(bytes: &[u8]) -> Self {
+        if let Some((encoding, bom_length)) = Encoding::for_bom(bytes) {
+            return Self { encoding, bom_length };
+        }
+
+        let sample = &bytes[..bytes.len().min(SNIFF_LENGTH)];
+        let zeros_at = |parity: usize| sample.iter().skip(parity).step_by(2).filter(|b| **b == 0).count();
+        let (even_zeros, odd_zeros) = (zeros_at(0), zeros_at(1));
+        let pairs = sample.len() / 2;
+        let encoding = if odd_zeros * 2 > pairs && even_zeros * 4 < pairs {
+            UTF_16LE
+        } else if even_zeros * 2 > pairs && odd_zeros * 4 < pairs {
+            UTF_16BE
+        } else {
+            WINDOWS_1252
+        };
+        Self { encoding, bom_length: 0 }
+    }
+
+    pub fn name(&self) -> &'static str {
+        self.encoding.name()
+    }
+
+    /// Decode a whole file, or None if it is not valid in this encoding
+    pub fn decode(&self, bytes: &[u8]) -> Option<String> {
+        self.encoding
+            .decode_without_bom_handling_and_without_replacement(bytes.get(self.bom_length..)?)
+            .map(Cow::into_owned)
+    }
+
+    /// Decode the start of a file for sniffing, replacing whatever doesn't decode
+    pub fn decode_lossy<'a>(&self, bytes: &'a [u8]) -> Cow<'a, str> {
+        self.encoding.decode_without_bom_handling(bytes.get(self.bom_length..).unwrap_or_default()).0
+    }
+
+    /// Encode text back into this encoding, including the byte order mark the file had
+    pub fn encode(&self, text: &str) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(text.len() + self.bom_length);
+        if self.encoding == UTF_16LE || self.encoding == UTF_16BE {
+            let little_endian = self.encoding == UTF_16LE;
+            if self.bom_length > 0 {
+                bytes.extend_from_slice(if little_endian { &[0xFF, 0xFE] } else { &[0xFE, 0xFF] });
+            }
+            for unit in text.encode_utf16() {
+                bytes.extend_from_slice(&if little_endian { unit.to_le_bytes() } else { unit.to_be_bytes() });
+            }
+        } else {
+            // encoding_rs only encodes UTF-16 as UTF-8, which is why it is handled above
+            if self.bom_length > 0 {
+                bytes.extend_from_slice(&[0xEF, 0xBB, 0xBF]);
+            }
+            bytes.extend_from_slice(&self.encoding.encode(text).0);
+        }
+        bytes
+    }
+}
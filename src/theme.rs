@@ -0,0 +1,50 @@
+use colored::{ColoredString, Colorize};
+use std::sync::atomic::{AtomicU8, Ordering};
+
+/// How console output tells the values a sync replaces from the ones it writes
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum Theme {
+    /// Old values in red, new values in green
+    #[default]
+    Color,
+    /// Old values prefixed with `-` and new values with `+` like in a diff, readable without
+    /// telling red from green
+    Symbols,
+    /// No colors and no markers
+    Plain,
+}
+
+static THEME: AtomicU8 = AtomicU8::new(Theme::Color as u8);
+
+/// Use `theme` for all console output of this process. `Plain` turns colors off as well.
+pub fn set(theme: Theme) {
+    THEME.store(theme as u8, Ordering::Relaxed);
+    if theme == Theme::Plain {
+        colored::control::set_override(false);
+    }
+}
+
+pub fn current() -> Theme {
+    match THEME.load(Ordering::Relaxed) {
+        1 => Theme::Symbols,
+        2 => Theme::Plain,
+        _ => Theme::Color,
+    }
+}
+
+/// A value being replaced, such as a subordinate GUID
+pub fn old(value: &str) -> ColoredString {
+    marked('-', value).red()
+}
+
+/// A value being written, such as a main GUID
+pub fn new(value: &str) -> ColoredString {
+    marked('+', value).green()
+}
+
+fn marked(symbol: char, value: &str) -> String {
+    match current() {
+        Theme::Symbols => format!("{}{}", symbol, value),
+        Theme::Color | Theme::Plain => value.to_string(),
+    }
+}
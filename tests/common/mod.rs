@@ -0,0 +1,84 @@
+//! Small main and subordinate projects built file by file in a temp dir, for the tests that
+//! need a project shaped just so rather than the shared fixtures.
+#![allow(dead_code)]
+
+use guid_sync::guid_mapper::DEFAULT_TOP;
+use guid_sync::{GuidSyncer, ScanOptions, SyncOptions, SyncReport};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+
+/// A GUID made of one repeated hex digit, e.g. `guid('a')`
+pub fn guid(digit: char) -> String {
+    digit.to_string().repeat(32)
+}
+
+/// A Unity YAML file with one MonoBehaviour whose body is `fields`
+pub fn yaml(fields: &str) -> String {
+    format!("%YAML 1.1\n%TAG !u! tag:unity3d.com,2011:\n--- !u!114 &1\nMonoBehaviour:\n{}", fields)
+}
+
+pub fn meta(guid: &str) -> String {
+    format!("fileFormatVersion: 2\nguid: {}\nDefaultImporter:\n  userData: \n", guid)
+}
+
+pub struct Projects {
+    pub dir: TempDir,
+}
+
+impl Projects {
+    pub fn new() -> Self {
+        let dir = tempfile::tempdir().expect("create temp dir");
+        fs::create_dir_all(dir.path().join("main/Assets")).unwrap();
+        fs::create_dir_all(dir.path().join("sub/Assets")).unwrap();
+        Self { dir }
+    }
+
+    pub fn main(&self) -> PathBuf {
+        self.dir.path().join("main/Assets")
+    }
+
+    pub fn sub(&self) -> PathBuf {
+        self.dir.path().join("sub/Assets")
+    }
+
+    /// Write a file, `path` being relative to the temp dir (`main/Assets/...` or `sub/Assets/...`)
+    pub fn write(&self, path: &str, contents: impl AsRef<[u8]>) -> PathBuf {
+        let path = self.dir.path().join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, contents).unwrap();
+        path
+    }
+
+    /// Add an asset to both projects, with `main_guid` in main and `sub_guid` in the subordinate
+    pub fn asset(&self, asset: &str, main_guid: &str, sub_guid: &str) {
+        for (project, guid) in [("main", main_guid), ("sub", sub_guid)] {
+            self.write(&format!("{}/Assets/{}", project, asset), "");
+            self.write(&format!("{}/Assets/{}.meta", project, asset), meta(guid));
+        }
+    }
+
+    pub fn read(&self, path: &str) -> String {
+        fs::read_to_string(self.dir.path().join(path)).unwrap()
+    }
+
+    pub fn syncer(&self, scan_options: ScanOptions) -> GuidSyncer {
+        let mut syncer = GuidSyncer::new(self.main(), self.sub()).with_scan_options(scan_options);
+        syncer.scan_projects().expect("scan projects");
+        syncer
+    }
+
+    /// Scan with the default options and run a live sync
+    pub fn sync(&self) -> SyncReport {
+        self.syncer(ScanOptions::default()).sync_guids(&sync_options()).expect("sync projects")
+    }
+}
+
+pub fn sync_options() -> SyncOptions {
+    SyncOptions { top: DEFAULT_TOP, ..Default::default() }
+}
+
+/// Relative path of `path` under `root`, for comparing report paths
+pub fn relative(root: &Path, path: &Path) -> PathBuf {
+    path.strip_prefix(root).unwrap_or(path).to_path_buf()
+}
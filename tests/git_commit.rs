@@ -0,0 +1,95 @@
+//! The `--commit` checks and commits made in a project's git repository.
+
+use git2::{Repository, Signature};
+use guid_sync::{git_commit, GuidSyncError};
+use std::fs;
+use std::path::Path;
+
+/// A repository with `Assets/Level.unity` and `Assets/Other.unity` committed
+fn repository(dir: &Path) -> Repository {
+    let repo = Repository::init(dir).unwrap();
+    let mut config = repo.config().unwrap();
+    config.set_str("user.name", "Tester").unwrap();
+    config.set_str("user.email", "tester@example.com").unwrap();
+    fs::create_dir_all(dir.join("Assets")).unwrap();
+    fs::write(dir.join("Assets/Level.unity"), "old").unwrap();
+    fs::write(dir.join("Assets/Other.unity"), "old").unwrap();
+    let mut index = repo.index().unwrap();
+    index.add_path(Path::new("Assets/Level.unity")).unwrap();
+    index.add_path(Path::new("Assets/Other.unity")).unwrap();
+    index.write().unwrap();
+    let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+    let signature = Signature::now("Tester", "tester@example.com").unwrap();
+    repo.commit(Some("HEAD"), &signature, &signature, "initial", &tree, &[]).unwrap();
+    drop(tree);
+    repo
+}
+
+fn committed(repo: &Repository, path: &str) -> String {
+    let tree = repo.head().unwrap().peel_to_tree().unwrap();
+    let blob = tree.get_path(Path::new(path)).unwrap().to_object(repo).unwrap().peel_to_blob().unwrap();
+    String::from_utf8(blob.content().to_vec()).unwrap()
+}
+
+#[test]
+fn uncommitted_changes_are_listed() {
+    let dir = tempfile::tempdir().unwrap();
+    repository(dir.path());
+    git_commit::ensure_clean(&dir.path().join("Assets")).unwrap();
+
+    fs::write(dir.path().join("Assets/Level.unity"), "changed").unwrap();
+    fs::write(dir.path().join("Assets/New.prefab"), "new").unwrap();
+    let error = git_commit::ensure_clean(&dir.path().join("Assets")).unwrap_err();
+    assert!(
+        matches!(&error, GuidSyncError::UncommittedChanges { changes, .. } if *changes == [" M Assets/Level.unity", "?? Assets/New.prefab"]),
+        "{}",
+        error
+    );
+
+    let outside = tempfile::tempdir().unwrap();
+    let error = git_commit::ensure_clean(outside.path()).unwrap_err();
+    assert!(matches!(error, GuidSyncError::NotInGitRepository { flag: "--commit", .. }), "{}", error);
+}
+
+#[test]
+fn only_the_named_files_are_committed() {
+    let dir = tempfile::tempdir().unwrap();
+    let repo = repository(dir.path());
+    fs::write(dir.path().join("Assets/Level.unity"), "synced").unwrap();
+    // Staged by someone else meanwhile, it must stay out of the commit
+    fs::write(dir.path().join("Assets/Other.unity"), "staged").unwrap();
+    let mut index = repo.index().unwrap();
+    index.add_path(Path::new("Assets/Other.unity")).unwrap();
+    index.write().unwrap();
+
+    let hash = git_commit::commit_files(dir.path(), &[dir.path().join("Assets/Level.unity")], "guid-sync: test").unwrap();
+
+    let head = repo.head().unwrap().peel_to_commit().unwrap();
+    assert!(head.id().to_string().starts_with(&hash));
+    assert_eq!(head.message(), Some("guid-sync: test"));
+    assert_eq!(committed(&repo, "Assets/Level.unity"), "synced");
+    assert_eq!(committed(&repo, "Assets/Other.unity"), "old");
+    let status = repo.status_file(Path::new("Assets/Other.unity")).unwrap();
+    assert!(status.contains(git2::Status::INDEX_MODIFIED));
+}
+
+#[test]
+fn changes_since_a_commit_include_untracked_and_deleted_files() {
+    let dir = tempfile::tempdir().unwrap();
+    repository(dir.path());
+    fs::write(dir.path().join("Assets/Level.unity"), "changed").unwrap();
+    fs::remove_file(dir.path().join("Assets/Other.unity")).unwrap();
+    fs::create_dir_all(dir.path().join("Assets/New")).unwrap();
+    fs::write(dir.path().join("Assets/New/Player.prefab"), "new").unwrap();
+    fs::write(dir.path().join("Outside.txt"), "new").unwrap();
+
+    let root = dir.path().canonicalize().unwrap();
+    let changed = git_commit::changed_since(&dir.path().join("Assets"), "HEAD").unwrap();
+    assert_eq!(
+        changed,
+        ["Assets/Level.unity", "Assets/New/Player.prefab", "Assets/Other.unity"].map(|path| root.join(path))
+    );
+
+    let error = git_commit::changed_since(dir.path(), "missing").unwrap_err();
+    assert!(matches!(&error, GuidSyncError::NotAGitCommit { rev, .. } if rev == "missing"), "{}", error);
+}
@@ -0,0 +1,80 @@
+//! Loading mapping files and plans, and applying them with `GuidSyncer::from_guid_pairs`.
+
+mod common;
+
+use common::{guid, sync_options, yaml, Projects};
+use guid_sync::mapping_file::{self, PlanOptions};
+use guid_sync::path_filter::PathFilter;
+use guid_sync::report_format::ReportFormat;
+use guid_sync::{GuidSyncError, GuidSyncer, ScanOptions};
+use std::fs;
+
+#[test]
+fn plan_is_applied_with_the_filters_it_was_made_with() {
+    let projects = Projects::new();
+    // Both metas share the subordinate GUID, so the plan lists the same pair twice
+    projects.asset("Player.prefab", &guid('a'), &guid('b'));
+    projects.asset("Copy.prefab", &guid('a'), &guid('b'));
+    let reference = |digit| yaml(&format!("  m_Prefab: {{fileID: 1, guid: {}, type: 3}}\n", guid(digit)));
+    projects.write("sub/Assets/Level.unity", reference('b'));
+    projects.write("sub/Assets/Ignored/Other.unity", reference('b'));
+
+    let plan_options = PlanOptions { exclude: vec!["Ignored/**".to_string()], ..Default::default() };
+    let syncer = projects.syncer(ScanOptions {
+        filter: PathFilter::new(&[], &plan_options.exclude).unwrap(),
+        ..Default::default()
+    });
+    let mut plan = syncer.generate_sync_operations_report(false).unwrap();
+    assert_eq!(plan.operations.len(), 2);
+    plan.options = Some(plan_options.clone());
+    let plan_path = projects.dir.path().join("plan.json");
+    fs::write(&plan_path, plan.render(ReportFormat::Json).unwrap()).unwrap();
+
+    let mapping = mapping_file::load_mapping(&plan_path).unwrap();
+    assert_eq!(mapping.pairs.len(), 1);
+    let options = mapping.options.expect("plan options");
+    assert_eq!(options, plan_options);
+    let mut sync_options = sync_options();
+    options.apply_to(&mut sync_options);
+    let applier = GuidSyncer::from_guid_pairs(projects.sub(), &mapping.pairs, options.scan_options().unwrap()).unwrap();
+    let report = applier.sync_guids(&sync_options).unwrap();
+
+    assert_eq!(report.total_references_replaced(), 1);
+    assert_eq!(projects.read("sub/Assets/Level.unity"), reference('a'));
+    assert_eq!(projects.read("sub/Assets/Ignored/Other.unity"), reference('b'));
+    assert_eq!(projects.read("sub/Assets/Copy.prefab.meta"), common::meta(&guid('a')));
+}
+
+#[test]
+fn duplicate_old_guid_needs_the_same_new_guid() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("mapping.csv");
+    fs::write(&path, format!("{b},{a}\n{b},{a}\n", a = guid('a'), b = guid('b'))).unwrap();
+    assert_eq!(mapping_file::load_guid_pairs(&path).unwrap().len(), 1);
+
+    fs::write(&path, format!("{b},{a}\n{b},{c}\n", a = guid('a'), b = guid('b'), c = guid('c'))).unwrap();
+    let error = mapping_file::load_guid_pairs(&path).unwrap_err();
+    assert!(matches!(&error, GuidSyncError::ConflictingMapping(old) if *old == guid('b')), "{}", error);
+}
+
+#[test]
+fn type_override_rewrites_guid_and_type() {
+    let projects = Projects::new();
+    let fields = |digit, texture_type| {
+        yaml(&format!(
+            "  m_Sprite: {{fileID: 21300000, guid: {g}, type: {t}}}\n  m_Plain: {{guid: {g}}}\n",
+            g = guid(digit),
+            t = texture_type
+        ))
+    };
+    projects.write("sub/Assets/Icon.prefab", fields('b', 2));
+    let path = projects.write("mapping.csv", format!("old_guid,new_guid,type\n{},{},3\n", guid('b'), guid('a')));
+
+    let pairs = mapping_file::load_guid_pairs(&path).unwrap();
+    assert_eq!(pairs[0].new_type, Some(3));
+    let syncer = GuidSyncer::from_guid_pairs(projects.sub(), &pairs, ScanOptions::default()).unwrap();
+    let report = syncer.sync_guids(&sync_options()).unwrap();
+
+    assert_eq!(report.total_references_replaced(), 2);
+    assert_eq!(projects.read("sub/Assets/Icon.prefab"), fields('a', 3));
+}
@@ -0,0 +1,77 @@
+//! Reading and rewriting single meta files with `MetaFile`.
+
+mod common;
+
+use common::guid;
+use guid_sync::{GuidSyncError, MetaFile};
+use std::fs;
+
+#[test]
+fn crlf_meta_file_keeps_its_line_endings() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("Player.png.meta");
+    let original = format!("fileFormatVersion: 2\r\nguid: {}\r\nTextureImporter:\r\n  userData: \r\n", guid('b'));
+    fs::write(&path, &original).unwrap();
+
+    assert_eq!(MetaFile::get_guid_from_file(&path).unwrap(), guid('b'));
+    MetaFile::update_guid_in_file(&path, &guid('a')).unwrap();
+
+    assert_eq!(fs::read_to_string(&path).unwrap(), original.replace(&guid('b'), &guid('a')));
+}
+
+#[test]
+fn bom_prefixed_meta_file_is_read_and_keeps_its_bom() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("Player.png.meta");
+    let original = format!("\u{feff}guid: {}\nfileFormatVersion: 2\n", guid('b'));
+    fs::write(&path, &original).unwrap();
+
+    assert_eq!(MetaFile::get_guid_from_file(&path).unwrap(), guid('b'));
+    MetaFile::update_guid_in_file(&path, &guid('a')).unwrap();
+
+    assert_eq!(fs::read_to_string(&path).unwrap(), format!("\u{feff}guid: {}\nfileFormatVersion: 2\n", guid('a')));
+}
+
+#[test]
+fn inline_comment_after_guid_is_ignored_and_kept() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("Player.png.meta");
+    fs::write(&path, format!("fileFormatVersion: 2\nguid: {}  # comment\n", guid('b'))).unwrap();
+
+    assert_eq!(MetaFile::get_guid_from_file(&path).unwrap(), guid('b'));
+    MetaFile::update_guid_in_file(&path, &guid('a')).unwrap();
+
+    assert_eq!(fs::read_to_string(&path).unwrap(), format!("fileFormatVersion: 2\nguid: {}  # comment\n", guid('a')));
+}
+
+#[test]
+fn folder_meta_is_recognised() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("Art.meta");
+    fs::write(&path, format!("fileFormatVersion: 2\nguid: {}\nfolderAsset: yes\nDefaultImporter:\n  userData: \n", guid('b'))).unwrap();
+
+    let info = MetaFile::parse(&path).unwrap();
+    assert_eq!(info.guid, guid('b'));
+    assert!(info.is_folder);
+    assert_eq!(info.importer.as_deref(), Some("DefaultImporter"));
+}
+
+#[test]
+fn malformed_guid_is_reported_with_its_value() {
+    let dir = tempfile::tempdir().unwrap();
+    let short = guid('a')[..31].to_string();
+    let non_hex = format!("{}g", &guid('a')[..31]);
+    for value in [short, non_hex] {
+        let path = dir.path().join("Player.png.meta");
+        fs::write(&path, format!("fileFormatVersion: 2\nguid: {}\n", value)).unwrap();
+
+        match MetaFile::get_guid_from_file(&path) {
+            Err(GuidSyncError::MalformedGuid { value: found, .. }) => assert_eq!(found, value),
+            other => panic!("expected a malformed GUID for {}, got {:?}", value, other),
+        }
+    }
+
+    let path = dir.path().join("Empty.png.meta");
+    fs::write(&path, "fileFormatVersion: 2\n").unwrap();
+    assert!(matches!(MetaFile::get_guid_from_file(&path), Err(GuidSyncError::MissingGuid(_))));
+}
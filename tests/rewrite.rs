@@ -0,0 +1,147 @@
+//! How references are rewritten inside reference files during a sync.
+
+mod common;
+
+use common::{guid, yaml, Projects};
+
+#[test]
+fn each_reference_is_rewritten_once_from_the_original_content() {
+    let projects = Projects::new();
+    // A -> B and B -> C: the B written for A must not be rewritten again to C
+    projects.asset("First.prefab", &guid('b'), &guid('a'));
+    projects.asset("Second.prefab", &guid('c'), &guid('b'));
+    projects.write(
+        "sub/Assets/Scene.unity",
+        yaml(&format!(
+            "  m_A: {{fileID: 1, guid: {a}, type: 3}}\n  m_Both: [{{guid: {a}}}, {{guid: {b}}}, {{guid: {a}}}]\n",
+            a = guid('a'),
+            b = guid('b')
+        )),
+    );
+
+    let report = projects.sync();
+
+    assert_eq!(report.total_references_replaced(), 4);
+    assert_eq!(
+        projects.read("sub/Assets/Scene.unity"),
+        yaml(&format!(
+            "  m_A: {{fileID: 1, guid: {b}, type: 3}}\n  m_Both: [{{guid: {b}}}, {{guid: {c}}}, {{guid: {b}}}]\n",
+            b = guid('b'),
+            c = guid('c')
+        ))
+    );
+}
+
+#[test]
+fn upper_case_meta_guid_matches_lower_case_references() {
+    let projects = Projects::new();
+    projects.asset("Player.prefab", &guid('a'), &guid('b').to_uppercase());
+    // Only the case differs, which is no difference at all
+    projects.asset("Same.prefab", &guid('c').to_uppercase(), &guid('c'));
+    projects.write("sub/Assets/Scene.unity", yaml(&format!("  m_Prefab: {{fileID: 1, guid: {}, type: 3}}\n", guid('b'))));
+
+    let syncer = projects.syncer(Default::default());
+    let differences = syncer.diff();
+    assert_eq!(differences.len(), 1);
+    assert_eq!(differences[0].sub_guid, guid('b'));
+
+    syncer.sync_guids(&common::sync_options()).expect("sync projects");
+    assert_eq!(
+        projects.read("sub/Assets/Scene.unity"),
+        yaml(&format!("  m_Prefab: {{fileID: 1, guid: {}, type: 3}}\n", guid('a')))
+    );
+}
+
+#[test]
+fn crlf_reference_file_keeps_its_line_endings() {
+    let projects = Projects::new();
+    projects.asset("Player.prefab", &guid('a'), &guid('b'));
+    let scene = yaml(&format!("  m_Prefab: {{fileID: 1, guid: {}, type: 3}}\n  m_Name: Level\n", guid('b'))).replace('\n', "\r\n");
+    projects.write("sub/Assets/Scene.unity", &scene);
+
+    projects.sync();
+
+    assert_eq!(projects.read("sub/Assets/Scene.unity"), scene.replace(&guid('b'), &guid('a')));
+}
+
+#[test]
+fn quoted_guid_references_keep_their_quotes() {
+    let projects = Projects::new();
+    projects.asset("Player.prefab", &guid('a'), &guid('b'));
+    let fields = |g: &str| format!("  m_Single: {{fileID: 1, guid: '{g}', type: 3}}\n  m_Double: {{fileID: 1, guid: \"{g}\", type: 3}}\n", g = g);
+    projects.write("sub/Assets/Quoted.asset", yaml(&fields(&guid('b'))));
+
+    let report = projects.sync();
+
+    assert_eq!(report.total_references_replaced(), 2);
+    assert_eq!(projects.read("sub/Assets/Quoted.asset"), yaml(&fields(&guid('a'))));
+}
+
+#[test]
+fn alternately_spaced_file_id_references_are_rewritten() {
+    let projects = Projects::new();
+    projects.asset("Player.prefab", &guid('a'), &guid('b'));
+    let fields = |g: &str| {
+        format!(
+            "  m_Tight: {{fileID:1,guid:{g},type:3}}\n  m_Loose: {{ fileID:  1 ,  guid:   {g} ,type: 3 }}\n  m_Wrapped: {{fileID: 1, guid: {g},\n    type: 3}}\n",
+            g = g
+        )
+    };
+    projects.write("sub/Assets/Spaced.asset", yaml(&fields(&guid('b'))));
+
+    let report = projects.sync();
+
+    assert_eq!(report.total_references_replaced(), 3);
+    assert_eq!(projects.read("sub/Assets/Spaced.asset"), yaml(&fields(&guid('a'))));
+}
+
+#[test]
+fn spacing_after_guid_key_is_preserved() {
+    let projects = Projects::new();
+    projects.asset("Player.prefab", &guid('a'), &guid('b'));
+    let fields = |g: &str| format!("\tm_Tabbed: {{fileID: 1, guid:\t{g}, type: 3}}\n  m_Double: {{fileID: 1, guid:  {g}, type: 3}}\n  guid: {g}  \n", g = g);
+    projects.write("sub/Assets/Spaced.asset", yaml(&fields(&guid('b'))));
+
+    let report = projects.sync();
+
+    assert_eq!(report.total_references_replaced(), 3);
+    assert_eq!(projects.read("sub/Assets/Spaced.asset"), yaml(&fields(&guid('a'))));
+}
+
+#[test]
+fn embedded_meta_reference_is_rewritten_but_not_the_meta_guid() {
+    let projects = Projects::new();
+    projects.asset("Player.prefab", &guid('a'), &guid('b'));
+    // A BOM must not hide that the first line is the meta file's own top-level guid
+    let sprite_meta = |own: &str, embedded: &str| {
+        format!(
+            "\u{feff}guid: {}\nfileFormatVersion: 2\nTextureImporter:\n  spritePackingTag: {{fileID: 11400000, guid: {}, type: 2}}\n",
+            own, embedded
+        )
+    };
+    projects.write("sub/Assets/Sprite.png", "");
+    projects.write("sub/Assets/Sprite.png.meta", sprite_meta(&guid('b'), &guid('b')));
+
+    let options = guid_sync::SyncOptions { rewrite_meta_refs: true, refs_only: true, ..common::sync_options() };
+    let report = projects.syncer(Default::default()).sync_guids(&options).expect("sync projects");
+
+    assert_eq!(report.total_references_replaced(), 1);
+    assert_eq!(projects.read("sub/Assets/Sprite.png.meta"), sprite_meta(&guid('b'), &guid('a')));
+}
+
+#[test]
+fn longer_hex_values_are_not_taken_for_a_guid() {
+    let projects = Projects::new();
+    projects.asset("Player.prefab", &guid('a'), &guid('b'));
+    // The changed GUID followed by more characters of the same word is some other value
+    let fields = format!("  m_Longer: {{guid: {b}0}}\n  m_Suffixed: {{guid: {b}_old}}\n  m_Exact: {{guid: {b}}}\n", b = guid('b'));
+    projects.write("sub/Assets/Values.asset", yaml(&fields));
+
+    let report = projects.sync();
+
+    assert_eq!(report.total_references_replaced(), 1);
+    assert_eq!(
+        projects.read("sub/Assets/Values.asset"),
+        yaml(&fields.replace(&format!("{{guid: {}}}", guid('b')), &format!("{{guid: {}}}", guid('a'))))
+    );
+}
@@ -0,0 +1,236 @@
+//! What a scan pairs up and reports, before anything is synced.
+
+mod common;
+
+use common::{guid, meta, relative, sync_options, yaml, Projects};
+use guid_sync::guid_mapper::{MatchMode, RiskLevel};
+use guid_sync::{GuidSyncer, ScanOptions};
+use std::fs;
+use std::path::PathBuf;
+use std::time::Duration;
+
+#[test]
+fn content_matching_pairs_a_moved_asset_with_equal_bytes() {
+    let projects = Projects::new();
+    projects.write("main/Assets/Art/Hero.png", "hero pixels");
+    projects.write("main/Assets/Art/Hero.png.meta", meta(&guid('a')));
+    projects.write("sub/Assets/Old/Hero.png", "hero pixels");
+    projects.write("sub/Assets/Old/Hero.png.meta", meta(&guid('b')));
+    projects.write("main/Assets/Art/Other.png", "other pixels");
+    projects.write("main/Assets/Art/Other.png.meta", meta(&guid('c')));
+    projects.write("sub/Assets/Old/Different.png", "different pixels");
+    projects.write("sub/Assets/Old/Different.png.meta", meta(&guid('d')));
+
+    let syncer = projects.syncer(ScanOptions { match_by: MatchMode::Content, ..Default::default() });
+    let differences = syncer.diff();
+    assert_eq!(differences.len(), 1);
+    assert_eq!(differences[0].relative_path, PathBuf::from("Old/Hero.png.meta"));
+    assert_eq!(differences[0].paired_with, Some(PathBuf::from("Art/Hero.png.meta")));
+    assert_eq!(differences[0].main_guid, guid('a'));
+}
+
+#[test]
+fn skip_folders_leaves_folder_guids_alone() {
+    let projects = Projects::new();
+    for (project, digit) in [("main", 'a'), ("sub", 'b')] {
+        projects.write(&format!("{}/Assets/Art.meta", project), format!("fileFormatVersion: 2\nguid: {}\nfolderAsset: yes\n", guid(digit)));
+    }
+    projects.asset("Art/Hero.png", &guid('c'), &guid('d'));
+
+    let differences = projects.syncer(ScanOptions::default()).diff();
+    assert_eq!(differences.len(), 2);
+
+    let differences = projects.syncer(ScanOptions { skip_folders: true, ..Default::default() }).diff();
+    assert_eq!(differences.len(), 1);
+    assert_eq!(differences[0].relative_path, PathBuf::from("Art/Hero.png.meta"));
+}
+
+#[test]
+fn operation_asset_name_keeps_every_dot_but_the_meta_extension() {
+    let projects = Projects::new();
+    projects.asset("UI/sprite.9.png", &guid('a'), &guid('b'));
+    projects.asset("Player.prefab", &guid('c'), &guid('d'));
+
+    let report = projects.syncer(ScanOptions::default()).generate_sync_operations_report(false).unwrap();
+    let names: Vec<&str> = report.operations.iter().map(|operation| operation.asset_name.as_str()).collect();
+    assert_eq!(names, ["Player.prefab", "sprite.9.png"]);
+}
+
+#[test]
+fn scan_cache_rereads_only_touched_metas() {
+    let projects = Projects::new();
+    projects.asset("Hero.png", &guid('a'), &guid('b'));
+    projects.asset("Enemy.png", &guid('c'), &guid('d'));
+    let cache = projects.dir.path().join("scan-cache.json");
+    let scan = || {
+        let mut syncer = GuidSyncer::new(projects.main(), projects.sub());
+        syncer.load_cache(&cache).unwrap();
+        syncer.scan_projects().unwrap();
+        syncer.save_cache(&cache).unwrap();
+        let mut main_guids: Vec<String> = syncer.diff().into_iter().map(|difference| difference.main_guid).collect();
+        main_guids.sort();
+        main_guids
+    };
+    assert_eq!(scan(), [guid('a'), guid('c')]);
+
+    // Enemy is rewritten behind the cache's back, keeping its modification time
+    let enemy = projects.main().join("Enemy.png.meta");
+    let modified = fs::metadata(&enemy).unwrap().modified().unwrap();
+    fs::write(&enemy, meta(&guid('e'))).unwrap();
+    fs::File::options().write(true).open(&enemy).unwrap().set_modified(modified).unwrap();
+    // Hero is touched
+    let hero = projects.main().join("Hero.png.meta");
+    fs::write(&hero, meta(&guid('f'))).unwrap();
+    let later = modified + Duration::from_secs(10);
+    fs::File::options().write(true).open(&hero).unwrap().set_modified(later).unwrap();
+
+    assert_eq!(scan(), [guid('c'), guid('f')]);
+}
+
+#[test]
+fn report_and_sync_pick_the_same_reference_files() {
+    let projects = Projects::new();
+    projects.asset("Player.prefab", &guid('a'), &guid('b'));
+    let reference = format!("  m_Prefab: {{fileID: 1, guid: {}, type: 3}}\n", guid('b'));
+    projects.write("sub/Assets/NoHeader.asset", &reference);
+    projects.write("sub/Assets/Notes.txt", yaml(&reference));
+    projects.write("sub/Assets/Level.unity", yaml(&reference));
+
+    let syncer = projects.syncer(ScanOptions::default());
+    let operations = syncer.generate_sync_operations_report(false).unwrap();
+    let mut reported: Vec<PathBuf> = operations.operations[0]
+        .reference_updates
+        .iter()
+        .map(|update| relative(&projects.sub(), &update.file_path))
+        .collect();
+    reported.sort();
+
+    let report = syncer.sync_guids(&sync_options()).unwrap();
+    let mut synced: Vec<PathBuf> = report.files_with_references().keys().cloned().collect();
+    synced.sort();
+
+    assert_eq!(reported, [PathBuf::from("Level.unity")]);
+    assert_eq!(synced, reported);
+    assert_eq!(projects.read("sub/Assets/NoHeader.asset"), reference);
+}
+
+#[test]
+fn scene_heavy_operation_outranks_a_material_only_one() {
+    let projects = Projects::new();
+    projects.asset("Albedo.png", &guid('a'), &guid('b'));
+    projects.asset("Player.prefab", &guid('c'), &guid('d'));
+    let references = |digit| format!("  m_A: {{fileID: 1, guid: {g}, type: 3}}\n  m_B: {{fileID: 1, guid: {g}, type: 3}}\n", g = guid(digit));
+    projects.write("sub/Assets/Skin.mat", yaml(&references('b')));
+    projects.write("sub/Assets/Level.unity", yaml(&references('d')));
+
+    let report = projects.syncer(ScanOptions::default()).generate_sync_operations_report(false).unwrap();
+    let ranked: Vec<(&str, usize, RiskLevel)> = report
+        .operations
+        .iter()
+        .map(|operation| (operation.asset_name.as_str(), operation.total_references, operation.risk))
+        .collect();
+    assert_eq!(ranked, [("Player.prefab", 2, RiskLevel::Medium), ("Albedo.png", 2, RiskLevel::Low)]);
+}
+
+#[test]
+fn meta_without_its_asset_is_orphaned_but_still_path_matched() {
+    let projects = Projects::new();
+    projects.asset("Kept.png", &guid('a'), &guid('b'));
+    fs::remove_file(projects.sub().join("Kept.png")).unwrap();
+    projects.write("sub/Assets/Old/Gone.png.meta", meta(&guid('c')));
+    projects.write("main/Assets/Art/Hero.png", "hero pixels");
+    projects.write("main/Assets/Art/Hero.png.meta", meta(&guid('d')));
+
+    let syncer = projects.syncer(ScanOptions { match_by: MatchMode::Content, ..Default::default() });
+    assert_eq!(syncer.orphaned_metas(), [PathBuf::from("Old/Gone.png.meta")]);
+    let differences = syncer.diff();
+    assert_eq!(differences.len(), 1);
+    assert_eq!(differences[0].relative_path, PathBuf::from("Kept.png.meta"));
+    assert_eq!(differences[0].paired_with, None);
+}
+
+#[cfg(unix)]
+#[test]
+fn symlinked_subfolder_is_processed_once() {
+    let projects = Projects::new();
+    projects.asset("Shared/Player.prefab", &guid('a'), &guid('b'));
+    projects.write(
+        "sub/Assets/Shared/Level.unity",
+        yaml(&format!("  m_Prefab: {{fileID: 1, guid: {}, type: 3}}\n", guid('b'))),
+    );
+    // The same real folder is reachable twice, and a link back to Assets forms a loop
+    std::os::unix::fs::symlink(projects.sub().join("Shared"), projects.sub().join("Linked")).unwrap();
+    std::os::unix::fs::symlink(projects.sub(), projects.sub().join("Shared/Loop")).unwrap();
+
+    let syncer = projects.syncer(ScanOptions { follow_symlinks: true, ..Default::default() });
+    assert_eq!(syncer.diff().len(), 1);
+    let report = syncer.sync_guids(&sync_options()).unwrap();
+
+    assert_eq!(report.guid_changes().len(), 1);
+    assert_eq!(report.total_references_replaced(), 1);
+    assert_eq!(report.files_with_references().len(), 1);
+}
+
+#[test]
+fn utf16_scene_is_reported_and_verified_like_it_is_synced() {
+    let projects = Projects::new();
+    projects.asset("Player.prefab", &guid('a'), &guid('b'));
+    let utf16 = |text: String| -> Vec<u8> {
+        [0xFF, 0xFE].into_iter().chain(text.encode_utf16().flat_map(u16::to_le_bytes)).collect()
+    };
+    let scene = |digit| yaml(&format!("  m_Prefab: {{fileID: 1, guid: {}, type: 3}}\n", guid(digit)));
+    projects.write("sub/Assets/Level.unity", utf16(scene('b')));
+    let scan_options = || ScanOptions { try_encodings: true, ..Default::default() };
+
+    let syncer = projects.syncer(scan_options());
+    let operations = syncer.generate_sync_operations_report(false).unwrap();
+    assert!(operations.skipped_files.is_empty());
+    assert_eq!(operations.operations[0].total_references, 1);
+    let stale = syncer.find_stale_references(None).unwrap();
+    assert_eq!(stale.len(), 1);
+    assert_eq!(stale[0].line_number, 5);
+
+    let report = syncer.sync_guids(&sync_options()).unwrap();
+    assert_eq!(report.total_references_replaced(), 1);
+    assert_eq!(fs::read(projects.sub().join("Level.unity")).unwrap(), utf16(scene('a')));
+    assert!(projects.syncer(scan_options()).find_stale_references(Some(&report)).unwrap().is_empty());
+}
+
+#[test]
+fn metas_sharing_a_guid_count_its_references_once() {
+    let projects = Projects::new();
+    // Both subordinate metas carry the same (corrupted) GUID, mapping to the same main GUID
+    projects.asset("Player.prefab", &guid('a'), &guid('b'));
+    projects.asset("PlayerCopy.prefab", &guid('a'), &guid('b'));
+    projects.write(
+        "sub/Assets/Level.unity",
+        yaml(&format!("  m_A: {{fileID: 1, guid: {g}, type: 3}}\n  m_B: {{fileID: 1, guid: {g}, type: 3}}\n", g = guid('b'))),
+    );
+
+    let syncer = projects.syncer(ScanOptions::default());
+    let report = syncer.generate_sync_operations_report(false).unwrap();
+    assert_eq!(report.operations.len(), 2);
+    assert_eq!(report.summary.total_reference_updates, 2);
+    assert_eq!(report.summary.total_files_with_references, 1);
+
+    let synced = syncer.sync_guids(&sync_options()).unwrap();
+    assert_eq!(synced.total_references_replaced(), report.summary.total_reference_updates);
+    assert_eq!(synced.guid_changes().len(), 2);
+}
+
+#[test]
+fn guid_prefix_also_narrows_content_matches() {
+    let projects = Projects::new();
+    projects.write("main/Assets/Art/Hero.png", "hero pixels");
+    projects.write("main/Assets/Art/Hero.png.meta", meta(&guid('a')));
+    projects.write("sub/Assets/Old/Hero.png", "hero pixels");
+    projects.write("sub/Assets/Old/Hero.png.meta", meta(&guid('b')));
+    projects.asset("Player.prefab", &guid('c'), &guid('d'));
+    let scan = |prefix: &str| {
+        let options = ScanOptions { match_by: MatchMode::Content, guid_prefix: Some(prefix.to_string()), ..Default::default() };
+        projects.syncer(options).diff().into_iter().map(|difference| difference.relative_path).collect::<Vec<_>>()
+    };
+
+    assert_eq!(scan("dd"), [PathBuf::from("Player.prefab.meta")]);
+    assert_eq!(scan("aa"), [PathBuf::from("Old/Hero.png.meta")]);
+}
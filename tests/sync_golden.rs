@@ -0,0 +1,528 @@
+//! Sync a small fixture project pair and compare the rewritten files byte for byte with the
+//! expected output in `tests/golden`.
+
+use guid_sync::guid_mapper::{AmbiguousResolution, DEFAULT_TOP};
+use guid_sync::guid_mapper::GuidChange;
+use guid_sync::mapping_file;
+use guid_sync::observer::ReferencePass;
+use guid_sync::report_format::ReportFormat;
+use guid_sync::{GuidSyncError, GuidSyncer, MetaFile, ScanOptions, SyncObserver, SyncOptions, SyncReport};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use tempfile::TempDir;
+use walkdir::WalkDir;
+
+const FIXTURES: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures");
+const GOLDEN: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/golden");
+
+/// Copy the fixture main and subordinate projects into a fresh temp dir
+fn fixture() -> TempDir {
+    let dir = tempfile::tempdir().expect("create temp dir");
+    for entry in WalkDir::new(FIXTURES) {
+        let entry = entry.expect("walk fixtures");
+        let target = dir.path().join(entry.path().strip_prefix(FIXTURES).unwrap());
+        if entry.file_type().is_dir() {
+            fs::create_dir_all(&target).unwrap();
+        } else {
+            fs::copy(entry.path(), &target).unwrap();
+        }
+    }
+    dir
+}
+
+fn scanned_syncer(dir: &Path) -> GuidSyncer {
+    let mut syncer = GuidSyncer::new(dir.join("main/Assets"), dir.join("sub/Assets"));
+    syncer.scan_projects().expect("scan fixture projects");
+    syncer
+}
+
+/// Every file under `root`, relative to it, with its contents
+fn read_tree(root: &Path) -> Vec<(PathBuf, Vec<u8>)> {
+    let mut files: Vec<(PathBuf, Vec<u8>)> = WalkDir::new(root)
+        .into_iter()
+        .map(|entry| entry.expect("walk tree"))
+        .filter(|entry| entry.file_type().is_file())
+        .map(|entry| {
+            let relative = entry.path().strip_prefix(root).unwrap().to_path_buf();
+            (relative, fs::read(entry.path()).unwrap())
+        })
+        .collect();
+    files.sort();
+    files
+}
+
+#[test]
+fn scan_finds_the_differing_guids() {
+    let dir = fixture();
+    let syncer = scanned_syncer(dir.path());
+
+    let mut differences: Vec<(PathBuf, String, String)> = syncer
+        .diff()
+        .into_iter()
+        .map(|difference| (difference.relative_path, difference.sub_guid, difference.main_guid))
+        .collect();
+    differences.sort();
+    assert_eq!(
+        differences,
+        vec![
+            (
+                PathBuf::from("Player.png.meta"),
+                "22222222222222222222222222222222".to_string(),
+                "11111111111111111111111111111111".to_string(),
+            ),
+            (
+                PathBuf::from("Prefabs/Player.prefab.meta"),
+                "bbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb".to_string(),
+                "aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa".to_string(),
+            ),
+            (
+                PathBuf::from("Scripts/Core.asmdef.meta"),
+                "ffffffffffffffffffffffffffffffff".to_string(),
+                "eeeeeeeeeeeeeeeeeeeeeeeeeeeeeeee".to_string(),
+            ),
+            (
+                PathBuf::from("Settings/Pipeline.asset.meta"),
+                "12121212121212121212121212121212".to_string(),
+                "34343434343434343434343434343434".to_string(),
+            ),
+            (
+                PathBuf::from("Sheet.png.meta"),
+                "77777777777777777777777777777777".to_string(),
+                "66666666666666666666666666666666".to_string(),
+            ),
+        ]
+    );
+}
+
+#[test]
+fn meta_importer_is_parsed_and_reported() {
+    let texture = MetaFile::parse(&Path::new(FIXTURES).join("sub/Assets/Player.png.meta")).unwrap();
+    assert_eq!(texture.importer.as_deref(), Some("TextureImporter"));
+
+    let dir = fixture();
+    let report = scanned_syncer(dir.path()).generate_sync_operations_report(false).unwrap();
+    let mut importers: Vec<(String, String)> = report
+        .operations
+        .into_iter()
+        .map(|op| (op.asset_name, op.asset_importer))
+        .collect();
+    importers.sort();
+    assert_eq!(
+        importers,
+        vec![
+            ("Core.asmdef".to_string(), "AssemblyDefinitionImporter".to_string()),
+            ("Pipeline.asset".to_string(), "NativeFormatImporter".to_string()),
+            ("Player.png".to_string(), "TextureImporter".to_string()),
+            ("Player.prefab".to_string(), "PrefabImporter".to_string()),
+            ("Sheet.png".to_string(), "TextureImporter".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn only_the_top_level_guid_of_a_sprite_sheet_is_used() {
+    let sheet = Path::new(FIXTURES).join("sub/Assets/Sheet.png.meta");
+    assert_eq!(MetaFile::get_guid_from_file(&sheet).unwrap(), "77777777777777777777777777777777");
+
+    // A nested 32-hex field ahead of the asset GUID is still not taken for it
+    let dir = tempfile::tempdir().unwrap();
+    let meta = dir.path().join("Nested.png.meta");
+    fs::write(
+        &meta,
+        "fileFormatVersion: 2\nTextureImporter:\n  sprites:\n  - spriteID: 5e97eb03825dee720800000000000000\n    guid: 33333333333333333333333333333333\nguid: 77777777777777777777777777777777\n",
+    )
+    .unwrap();
+    assert_eq!(MetaFile::get_guid_from_file(&meta).unwrap(), "77777777777777777777777777777777");
+
+    MetaFile::update_guid_in_file(&meta, "66666666666666666666666666666666").unwrap();
+    let updated = fs::read_to_string(&meta).unwrap();
+    assert!(updated.contains("    guid: 33333333333333333333333333333333\n"));
+    assert!(updated.contains("spriteID: 5e97eb03825dee720800000000000000"));
+    assert!(updated.ends_with("\nguid: 66666666666666666666666666666666\n"));
+}
+
+#[test]
+fn sync_matches_golden_files() {
+    let dir = fixture();
+    let syncer = scanned_syncer(dir.path());
+    let report = syncer
+        .sync_guids(&SyncOptions { top: DEFAULT_TOP, ..Default::default() })
+        .expect("sync fixture projects");
+
+    assert_eq!(report.guid_changes().len(), 5);
+    assert_eq!(report.total_references_replaced(), 10);
+
+    let sub = dir.path().join("sub/Assets");
+    let golden = Path::new(GOLDEN).join("sub/Assets");
+    for (relative, expected) in read_tree(&golden) {
+        let actual = fs::read(sub.join(&relative)).unwrap();
+        assert!(
+            actual == expected,
+            "{} differs from its golden file:\n{}",
+            relative.display(),
+            String::from_utf8_lossy(&actual)
+        );
+    }
+
+    // Files without rewritten GUIDs are left exactly as they were
+    let fixture_sub = Path::new(FIXTURES).join("sub/Assets");
+    for (relative, original) in read_tree(&fixture_sub) {
+        if !golden.join(&relative).exists() {
+            assert_eq!(fs::read(sub.join(&relative)).unwrap(), original, "{} changed", relative.display());
+        }
+    }
+
+    // The assembly definition's `GUID:` reference is rewritten and it stays valid JSON
+    let asmdef = fs::read(sub.join("Scripts/Game.asmdef")).unwrap();
+    serde_json::from_slice::<serde_json::Value>(&asmdef).expect("Game.asmdef is valid JSON");
+
+    // Synced projects no longer differ
+    assert_eq!(scanned_syncer(dir.path()).get_difference_count(), 0);
+}
+
+#[test]
+fn project_settings_references_are_only_rewritten_when_included() {
+    let dir = fixture();
+    let settings = dir.path().join("sub/ProjectSettings/GraphicsSettings.asset");
+    let original = fs::read(&settings).unwrap();
+    scanned_syncer(dir.path())
+        .sync_guids(&SyncOptions { top: DEFAULT_TOP, ..Default::default() })
+        .expect("sync fixture projects");
+    assert_eq!(fs::read(&settings).unwrap(), original);
+
+    let dir = fixture();
+    let settings = dir.path().join("sub/ProjectSettings/GraphicsSettings.asset");
+    let mut syncer = GuidSyncer::new(dir.path().join("main/Assets"), dir.path().join("sub/Assets"))
+        .with_scan_options(ScanOptions { include_project_settings: true, ..Default::default() });
+    syncer.scan_projects().expect("scan fixture projects");
+    // The settings files have no meta files, so they add no differences of their own
+    assert_eq!(syncer.get_difference_count(), 5);
+
+    let operations = syncer.generate_sync_operations_report(false).unwrap();
+    let pipeline = operations.operations.iter().find(|op| op.asset_name == "Pipeline.asset").unwrap();
+    assert_eq!(pipeline.total_references, 1);
+    assert_eq!(pipeline.reference_updates[0].file_path, Path::new("../ProjectSettings/GraphicsSettings.asset"));
+
+    let backup = dir.path().join("backup");
+    syncer
+        .sync_guids(&SyncOptions { backup_dir: Some(backup.clone()), top: DEFAULT_TOP, ..Default::default() })
+        .expect("sync fixture projects");
+    assert_eq!(fs::read(&settings).unwrap(), fs::read(Path::new(GOLDEN).join("sub/ProjectSettings/GraphicsSettings.asset")).unwrap());
+    assert_eq!(fs::read(backup.join("ProjectSettings~/GraphicsSettings.asset")).unwrap(), original);
+}
+
+/// Records every observer call as a line of text
+#[derive(Default)]
+struct RecordingObserver(Mutex<Vec<String>>);
+
+impl SyncObserver for RecordingObserver {
+    fn on_file_scanned(&self, meta_path: &Path) {
+        self.0.lock().unwrap().push(format!("scanned {}", meta_path.display()));
+    }
+
+    fn on_meta_updated(&self, change: &GuidChange) {
+        self.0.lock().unwrap().push(format!("meta {} {}", change.meta_path.display(), change.new_guid));
+    }
+
+    fn on_scan_finished(&self, project: &Path, files: usize) {
+        let name = project.parent().unwrap().file_name().unwrap().to_string_lossy();
+        self.0.lock().unwrap().push(format!("scan {} {}", name, files));
+    }
+
+    fn on_references_started(&self, pass: ReferencePass) {
+        self.0.lock().unwrap().push(format!("{:?}", pass));
+    }
+
+    fn on_reference_replaced(&self, file: &Path, count: usize) {
+        self.0.lock().unwrap().push(format!("references {} {}", file.file_name().unwrap().to_string_lossy(), count));
+    }
+
+    fn on_sync_finished(&self, report: &SyncReport, _options: &SyncOptions) {
+        self.0.lock().unwrap().push(format!("finished {}", report.summary_line()));
+    }
+}
+
+#[test]
+fn observer_sees_scanned_metas_and_rewritten_files() {
+    let dir = fixture();
+    let observer = Arc::new(RecordingObserver::default());
+    let mut syncer = GuidSyncer::new(dir.path().join("main/Assets"), dir.path().join("sub/Assets"))
+        .with_observer(observer.clone());
+    syncer.scan_projects().expect("scan fixture projects");
+    let scanned = observer.0.lock().unwrap().len();
+    // Both projects have the same eight meta files
+    assert_eq!(scanned, 18);
+    assert_eq!(observer.0.lock().unwrap()[scanned - 1], "scan sub 8");
+
+    syncer
+        .sync_guids(&SyncOptions { dry_run: true, top: DEFAULT_TOP, ..Default::default() })
+        .expect("dry run fixture projects");
+    let events = observer.0.lock().unwrap()[scanned..].to_vec();
+    assert_eq!(
+        events,
+        vec![
+            "meta Player.png.meta 11111111111111111111111111111111",
+            "meta Prefabs/Player.prefab.meta aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa",
+            "meta Scripts/Core.asmdef.meta eeeeeeeeeeeeeeeeeeeeeeeeeeeeeeee",
+            "meta Settings/Pipeline.asset.meta 34343434343434343434343434343434",
+            "meta Sheet.png.meta 66666666666666666666666666666666",
+            "Rewrite { files: 6 }",
+            "references Level.unity 4",
+            "references Main.unity 4",
+            "references Player.prefab 1",
+            "references Game.asmdef 1",
+            "finished 5 meta files, 10 references in 4 files",
+        ]
+    );
+}
+
+#[test]
+fn scene_prefab_instances_and_stripped_objects_are_rewritten() {
+    let dir = fixture();
+    let syncer = scanned_syncer(dir.path());
+    let report = syncer
+        .sync_guids(&SyncOptions { top: DEFAULT_TOP, ..Default::default() })
+        .expect("sync fixture projects");
+
+    // The prefab instance's modification target and source prefab, the stripped transform's
+    // source object split over two lines, and the render settings texture
+    let level = dir.path().join("sub/Assets/Level.unity");
+    assert_eq!(report.files_with_references().get(Path::new("Level.unity")), Some(&4));
+    let content = fs::read_to_string(&level).unwrap();
+    assert!(content.contains("m_CorrespondingSourceObject: {fileID: 1003, guid: aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa,\n    type: 3}"));
+    // Built-in resources, the occlusion data GUID and the fileID-only scene roots stay as they are
+    assert!(content.contains("guid: 0000000000000000f000000000000000, type: 0"));
+    assert!(content.contains("m_SceneGUID: 00000000000000000000000000000000"));
+    assert!(content.contains("SceneRoots:\n  m_ObjectHideFlags: 0\n  m_Roots:\n  - {fileID: 3002}"));
+}
+
+#[test]
+fn files_without_changed_guids_are_skipped_and_upper_case_references_still_found() {
+    let dir = fixture();
+    let sub = dir.path().join("sub/Assets");
+    // Only references GUIDs that stay the same, so the prefilter passes over it
+    let unrelated = "%YAML 1.1\n--- !u!114 &1\nMonoBehaviour:\n  m_Script: {fileID: 11500000, guid: 0123456789abcdef0123456789abcdef, type: 3}\n";
+    fs::write(sub.join("Unrelated.asset"), unrelated).unwrap();
+    fs::write(
+        sub.join("Upper.asset"),
+        "%YAML 1.1\n--- !u!114 &1\nMonoBehaviour:\n  m_Prefab: {fileID: 100100000, guid: BBBBBBBBBBBBBBBBBBBBBBBBBBBBBBBB, type: 3}\n",
+    )
+    .unwrap();
+
+    let syncer = scanned_syncer(dir.path());
+    let operations = syncer.generate_sync_operations_report(false).unwrap();
+    let prefab = operations.operations.iter().find(|op| op.asset_name == "Player.prefab").unwrap();
+    assert!(prefab.reference_updates.iter().any(|update| update.file_path == Path::new("Upper.asset")));
+    assert!(operations
+        .operations
+        .iter()
+        .flat_map(|op| &op.reference_updates)
+        .all(|update| update.file_path != Path::new("Unrelated.asset")));
+
+    let report = syncer
+        .sync_guids(&SyncOptions { top: DEFAULT_TOP, ..Default::default() })
+        .expect("sync fixture projects");
+    assert_eq!(report.total_references_replaced(), 11);
+    assert_eq!(fs::read_to_string(sub.join("Unrelated.asset")).unwrap(), unrelated);
+    assert!(fs::read_to_string(sub.join("Upper.asset")).unwrap().contains("guid: aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa, type: 3"));
+}
+
+#[test]
+fn only_file_rewrites_just_that_file() {
+    let dir = fixture();
+    let before = read_tree(dir.path());
+    let scene = dir.path().join("sub/Assets/Main.unity");
+
+    let syncer = scanned_syncer(dir.path());
+    let report = syncer
+        .sync_guids(&SyncOptions { only_file: Some(scene.clone()), top: DEFAULT_TOP, ..Default::default() })
+        .expect("sync a single file");
+    assert!(report.guid_changes().is_empty());
+
+    assert_eq!(fs::read(&scene).unwrap(), fs::read(Path::new(GOLDEN).join("sub/Assets/Main.unity")).unwrap());
+    let changed: Vec<PathBuf> = read_tree(dir.path())
+        .into_iter()
+        .zip(before)
+        .filter(|(after, before)| after != before)
+        .map(|(after, _)| after.0)
+        .collect();
+    assert_eq!(changed, vec![PathBuf::from("sub/Assets/Main.unity")]);
+}
+
+const SHARED_GUID: &str = "abababababababababababababababab";
+
+/// Fixture where two subordinate meta files share a GUID, but their main counterparts differ
+fn ambiguous_fixture() -> TempDir {
+    let dir = fixture();
+    for (name, main_guid) in [("A.asset", "88888888888888888888888888888888"), ("B.asset", "99999999999999999999999999999999")] {
+        for (side, guid) in [("main", main_guid), ("sub", SHARED_GUID)] {
+            let assets = dir.path().join(side).join("Assets");
+            fs::write(assets.join(name), "%YAML 1.1\n--- !u!114 &1\nMonoBehaviour:\n  m_Name: x\n").unwrap();
+            fs::write(assets.join(format!("{}.meta", name)), format!("fileFormatVersion: 2\nguid: {}\n", guid)).unwrap();
+        }
+    }
+    let reference = format!("%YAML 1.1\n--- !u!114 &1\nMonoBehaviour:\n  m_Data: {{fileID: 11400000, guid: {}, type: 2}}\n", SHARED_GUID);
+    fs::write(dir.path().join("sub/Assets/Ref.asset"), reference).unwrap();
+    dir
+}
+
+#[test]
+fn ambiguous_sub_guid_is_refused_unless_resolved() {
+    let dir = ambiguous_fixture();
+    let syncer = scanned_syncer(dir.path());
+    let ambiguous = syncer.ambiguous_guids();
+    assert_eq!(ambiguous.len(), 1);
+    assert_eq!(ambiguous[0].sub_guid, SHARED_GUID);
+    assert_eq!(
+        ambiguous[0].candidates,
+        vec![
+            (PathBuf::from("A.asset.meta"), "88888888888888888888888888888888".to_string()),
+            (PathBuf::from("B.asset.meta"), "99999999999999999999999999999999".to_string()),
+        ]
+    );
+
+    let before = read_tree(dir.path());
+    let error = syncer.sync_guids(&SyncOptions { top: DEFAULT_TOP, ..Default::default() }).unwrap_err();
+    assert!(matches!(error, GuidSyncError::AmbiguousGuids { count: 1 }), "{}", error);
+    assert_eq!(read_tree(dir.path()), before);
+
+    // `first` maps the references like the first meta file in path order
+    syncer
+        .sync_guids(&SyncOptions { resolve_ambiguous: Some(AmbiguousResolution::First), top: DEFAULT_TOP, ..Default::default() })
+        .expect("sync resolving to the first candidate");
+    let sub = dir.path().join("sub/Assets");
+    assert!(fs::read_to_string(sub.join("Ref.asset")).unwrap().contains("guid: 88888888888888888888888888888888"));
+    assert_eq!(MetaFile::get_guid_from_file(&sub.join("A.asset.meta")).unwrap(), "88888888888888888888888888888888");
+    assert_eq!(MetaFile::get_guid_from_file(&sub.join("B.asset.meta")).unwrap(), "99999999999999999999999999999999");
+
+    // `skip` leaves the shared GUID alone and syncs everything else
+    let dir = ambiguous_fixture();
+    let sub = dir.path().join("sub/Assets");
+    let reference = fs::read(sub.join("Ref.asset")).unwrap();
+    scanned_syncer(dir.path())
+        .sync_guids(&SyncOptions { resolve_ambiguous: Some(AmbiguousResolution::Skip), top: DEFAULT_TOP, ..Default::default() })
+        .expect("sync skipping the ambiguous GUID");
+    assert_eq!(fs::read(sub.join("Ref.asset")).unwrap(), reference);
+    for name in ["A.asset.meta", "B.asset.meta"] {
+        assert_eq!(MetaFile::get_guid_from_file(&sub.join(name)).unwrap(), SHARED_GUID);
+    }
+    assert_eq!(MetaFile::get_guid_from_file(&sub.join("Player.png.meta")).unwrap(), "11111111111111111111111111111111");
+}
+
+#[test]
+fn plan_resolving_ambiguous_guids_to_the_first_can_be_applied() {
+    let dir = ambiguous_fixture();
+    let options = SyncOptions { resolve_ambiguous: Some(AmbiguousResolution::First), top: DEFAULT_TOP, ..Default::default() };
+    let plan = scanned_syncer(dir.path()).generate_plan(&options).expect("generate plan");
+    let shared: Vec<&Path> = plan.operations.iter().filter(|op| op.old_guid == SHARED_GUID).map(|op| op.asset_path.as_path()).collect();
+    assert_eq!(shared, [Path::new("A.asset.meta")]);
+
+    let plan_path = dir.path().join("plan.json");
+    fs::write(&plan_path, plan.render(ReportFormat::Json).unwrap()).unwrap();
+    let mapping = mapping_file::load_mapping(&plan_path).expect("plan is a valid mapping");
+    let sub = dir.path().join("sub/Assets");
+    GuidSyncer::new(PathBuf::new(), sub.clone())
+        .with_guid_pairs(&mapping.pairs)
+        .unwrap()
+        .sync_guids(&SyncOptions { top: DEFAULT_TOP, ..Default::default() })
+        .expect("apply the plan");
+
+    assert!(fs::read_to_string(sub.join("Ref.asset")).unwrap().contains("guid: 88888888888888888888888888888888"));
+    for name in ["A.asset.meta", "B.asset.meta"] {
+        assert_eq!(MetaFile::get_guid_from_file(&sub.join(name)).unwrap(), "88888888888888888888888888888888");
+    }
+    assert_eq!(MetaFile::get_guid_from_file(&sub.join("Player.png.meta")).unwrap(), "11111111111111111111111111111111");
+}
+
+#[test]
+fn dangling_references_are_found_and_built_in_guids_ignored() {
+    let dir = fixture();
+    fs::write(
+        dir.path().join("sub/Assets/Broken.mat"),
+        "%YAML 1.1\n--- !u!21 &1\nMaterial:\n  m_Shader: {fileID: 46, guid: 0000000000000000f000000000000000, type: 0}\n  m_Tex: {fileID: 2800000, guid: DEADBEEFDEADBEEFDEADBEEFDEADBEEF, type: 3}\n",
+    )
+    .unwrap();
+
+    let syncer = scanned_syncer(dir.path());
+    let mut report = syncer.generate_sync_operations_report(false).unwrap();
+    syncer.add_dangling_references(&mut report);
+    let dangling: Vec<(String, Vec<(PathBuf, usize)>)> = report
+        .dangling_references
+        .into_iter()
+        .map(|dangling| (dangling.guid, dangling.files))
+        .collect();
+    assert_eq!(
+        dangling,
+        vec![
+            // The fixture scene's script lives outside both projects
+            ("dddddddddddddddddddddddddddddddd".to_string(), vec![(PathBuf::from("Main.unity"), 1)]),
+            ("deadbeefdeadbeefdeadbeefdeadbeef".to_string(), vec![(PathBuf::from("Broken.mat"), 1)]),
+        ]
+    );
+}
+
+#[test]
+fn dry_run_changes_no_files() {
+    let dir = fixture();
+    let before = read_tree(dir.path());
+
+    let syncer = scanned_syncer(dir.path());
+    let report = syncer
+        .sync_guids(&SyncOptions { dry_run: true, top: DEFAULT_TOP, ..Default::default() })
+        .expect("dry run fixture projects");
+
+    assert_eq!(report.total_references_replaced(), 10);
+    assert_eq!(read_tree(dir.path()), before);
+}
+
+#[test]
+fn read_only_file_is_skipped_and_the_rest_synced() {
+    let dir = fixture();
+    let scene = dir.path().join("sub/Assets/Main.unity");
+    let mut permissions = fs::metadata(&scene).unwrap().permissions();
+    permissions.set_readonly(true);
+    fs::set_permissions(&scene, permissions).unwrap();
+
+    let syncer = scanned_syncer(dir.path());
+    let report = syncer
+        .sync_guids(&SyncOptions { top: DEFAULT_TOP, ..Default::default() })
+        .expect("a read-only file does not stop the sync");
+
+    let failed: Vec<&Path> = report.failed_files().iter().map(|(path, _)| path.as_path()).collect();
+    assert_eq!(failed, vec![scene.as_path()]);
+    assert_eq!(fs::read(&scene).unwrap(), fs::read(Path::new(FIXTURES).join("sub/Assets/Main.unity")).unwrap());
+
+    let golden = Path::new(GOLDEN).join("sub/Assets");
+    for relative in ["Prefabs/Player.prefab", "Prefabs/Player.prefab.meta", "Player.png.meta", "Scripts/Game.asmdef"] {
+        let actual = fs::read(dir.path().join("sub/Assets").join(relative)).unwrap();
+        assert!(actual == fs::read(golden.join(relative)).unwrap(), "{} differs from its golden file", relative);
+    }
+}
+
+#[test]
+fn references_after_a_megabyte_line_keep_their_line_numbers() {
+    // Serialized mesh data puts megabytes of hex on one line; with thousands of references
+    // after it, looking up each reference's line must not rescan the file
+    const REFERENCES: usize = 5000;
+    let dir = fixture();
+    let mut mesh = format!("%YAML 1.1\n--- !u!43 &4300000\nMesh:\n  _typelessdata: {}\n", "0000803f".repeat(1 << 20));
+    for i in 0..REFERENCES {
+        mesh.push_str(&format!("  m_Ref{}: {{fileID: 11400000, guid: bbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb, type: 3}}\n", i));
+    }
+    fs::write(dir.path().join("sub/Assets/Mesh.asset"), &mesh).unwrap();
+
+    let report = scanned_syncer(dir.path()).generate_sync_operations_report(true).unwrap();
+    let prefab = report.operations.iter().find(|op| op.asset_name == "Player.prefab").unwrap();
+    let update = prefab
+        .reference_updates
+        .iter()
+        .find(|update| update.file_path.ends_with("Mesh.asset"))
+        .unwrap();
+    assert_eq!(update.reference_count, REFERENCES);
+
+    let details = update.references.as_ref().unwrap();
+    let line_numbers: Vec<usize> = details.iter().map(|detail| detail.line_number).collect();
+    assert_eq!(line_numbers, (5..5 + REFERENCES).collect::<Vec<_>>());
+    assert!(details[0].context_snippet.starts_with("m_Ref0: {fileID: 11400000"));
+}
@@ -0,0 +1,90 @@
+//! The reports a sync exports, and reading them back for `undo` and `verify`.
+
+mod common;
+
+use common::{guid, sync_options, Projects};
+use guid_sync::guid_mapper::CombinedSyncReport;
+use guid_sync::{GuidSyncError, GuidSyncer, MetaFile, SyncReport};
+
+#[test]
+fn combined_report_can_be_undone_per_project() {
+    let projects = Projects::new();
+    projects.asset("Player.prefab", &guid('a'), &guid('b'));
+    projects.write("other/Assets/Player.prefab", "");
+    projects.write("other/Assets/Player.prefab.meta", common::meta(&guid('c')));
+    let other = projects.dir.path().join("other");
+
+    let mut combined = CombinedSyncReport::default();
+    combined.add(projects.dir.path().join("sub"), projects.sync());
+    let mut other_syncer = GuidSyncer::new(projects.main(), other.join("Assets"));
+    other_syncer.scan_projects().unwrap();
+    combined.add(other.clone(), other_syncer.sync_guids(&sync_options()).unwrap());
+    let report_path = projects.dir.path().join("report.json");
+    combined.export_to_file(&report_path).unwrap();
+
+    // Assets or the project folder both name the project
+    let report = SyncReport::load_for_project(&report_path, &other.join("Assets")).unwrap();
+    assert_eq!(report.guid_changes()[0].old_guid, guid('c'));
+    GuidSyncer::from_sync_report(other.join("Assets"), &report).sync_guids(&sync_options()).unwrap();
+    assert_eq!(MetaFile::get_guid_from_file(&other.join("Assets/Player.prefab.meta")).unwrap(), guid('c'));
+
+    let report = SyncReport::load_for_project(&report_path, &projects.dir.path().join("sub")).unwrap();
+    assert_eq!(report.guid_changes()[0].old_guid, guid('b'));
+
+    let missing = SyncReport::load_for_project(&report_path, &projects.main());
+    assert!(matches!(missing, Err(GuidSyncError::ProjectNotInReport { .. })));
+}
+
+#[test]
+fn single_project_report_loads_for_its_project() {
+    let projects = Projects::new();
+    projects.asset("Player.prefab", &guid('a'), &guid('b'));
+    let report_path = projects.dir.path().join("report.json");
+    projects.sync().export_to_file(&report_path).unwrap();
+
+    let report = SyncReport::load_for_project(&report_path, &projects.sub()).unwrap();
+    assert_eq!(report.guid_changes().len(), 1);
+}
+
+#[test]
+fn equal_length_guid_change_adds_no_bytes() {
+    let projects = Projects::new();
+    projects.asset("Player.prefab", &guid('a'), &guid('b'));
+    let meta = projects.sub().join("Player.prefab.meta");
+    let scene = projects.write(
+        "sub/Assets/Levels/Level.unity",
+        common::yaml(&format!("  m_Prefab: {{fileID: 1, guid: {}, type: 3}}\n", guid('b'))),
+    );
+    let modified_size = std::fs::metadata(&meta).unwrap().len() + std::fs::metadata(&scene).unwrap().len();
+
+    let report = projects.sync();
+
+    assert_eq!(report.size_line(), format!("{0} -> {0} bytes (+0) in 2 directories", modified_size));
+    let report_path = projects.dir.path().join("report.json");
+    report.export_to_file(&report_path).unwrap();
+    let json: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&report_path).unwrap()).unwrap();
+    assert_eq!(json["total_bytes_before"], modified_size);
+    assert_eq!(json["total_bytes_after"], modified_size);
+}
+
+#[test]
+fn two_runs_list_changes_in_the_same_path_order() {
+    let projects = Projects::new();
+    for (name, main, sub) in [("Zeta.prefab", 'a', 'b'), ("Alpha/Beta.png", 'c', 'd'), ("Mid.mat", 'e', 'f'), ("Alpha.asset", '1', '2')] {
+        projects.asset(name, &guid(main), &guid(sub));
+    }
+    let dry_run = || {
+        let syncer = projects.syncer(Default::default());
+        let differences: Vec<_> = syncer.diff().into_iter().map(|difference| difference.relative_path).collect();
+        let report = syncer.sync_guids(&guid_sync::SyncOptions { dry_run: true, ..sync_options() }).unwrap();
+        let changes: Vec<_> = report.guid_changes().iter().map(|change| change.meta_path.clone()).collect();
+        (differences, changes)
+    };
+
+    let (differences, changes) = dry_run();
+    let mut sorted = differences.clone();
+    sorted.sort();
+    assert_eq!(differences, sorted);
+    assert_eq!(changes.iter().map(|path| common::relative(&projects.sub(), path)).collect::<Vec<_>>(), sorted);
+    assert_eq!(dry_run(), (differences, changes));
+}